@@ -7,7 +7,7 @@ def find_command():
     project_root = here.parent.parent.parent.parent.parent
     return str(project_root / 'target' / 'release' / 'cargo-listdoc')
 
-COMMAND = ['cargo', 'listdoc', 'location']
+COMMAND = ['cargo', 'listdoc', 'open', '--stdin']
 COMMAND[0] = find_command()
 
 class Kind(Base):
@@ -17,8 +17,10 @@ class Kind(Base):
         self.default_action = 'browse'
 
     def action_browse(self, context):
-        for t in context['targets']:
-            path = subprocess.Popen(
-                COMMAND + [t['action__path']],
-                stdout=subprocess.PIPE).stdout.read().decode('utf-8').rstrip()
-            self.vim.command('call rustdoc_index#open_denite("{}")'.format(path))
+        lines = ''.join(t['action__path'] + '\n' for t in context['targets'])
+        err = subprocess.run(
+            COMMAND, input=lines.encode('utf-8'),
+            stdout=subprocess.DEVNULL,
+            stderr=subprocess.PIPE).stderr.decode('utf-8')
+        for line in err.splitlines():
+            self.vim.call('denite#util#print_error', line)
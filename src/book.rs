@@ -0,0 +1,191 @@
+//! Chapters and headings of the mdBook books shipped with rust-docs
+//!
+//! Books such as `reference/` and `nomicon/` sit next to `std/` with a `searchindex.json`.
+use crate::Error;
+use rayon::prelude::*;
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf}
+};
+
+/// The kind of book lines, in place of an item type
+pub const SECTION: &str = "section";
+
+/// A heading of a book, or the first heading of a chapter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// `reference::items::traits` for a chapter, `reference::items::traits::object-safety`
+    /// for a heading in it
+    pub path: String,
+    pub title: String,
+    /// The page relative to the doc dir
+    pub file: PathBuf,
+    pub anchor: Option<String>
+}
+
+impl Section {
+    pub fn line(&self) -> String { format!("{}\t{}", self.path, SECTION) }
+}
+
+/// Whether the line of list is of a book
+pub fn is_section(line: &str) -> bool { line.split_whitespace().nth(1) == Some(SECTION) }
+
+/// `searchindex.json`, or `searchindex.js` that assigns the same object
+pub fn index_file(book_dir: &Path) -> Option<PathBuf> {
+    let mut found = book_dir
+        .read_dir()
+        .ok()?
+        .filter_map(|e| e.ok()?.file_name().into_string().ok())
+        .filter(|name| {
+            name.starts_with("searchindex") && (name.ends_with(".json") || name.ends_with(".js"))
+        })
+        .collect::<Vec<_>>();
+    // JSON is quicker to parse than the script
+    found.sort_by_key(|name| !name.ends_with(".json"));
+    found.first().map(|name| book_dir.join(name))
+}
+
+/// Books in the doc dir and their search indexes
+///
+/// The error index is an mdBook too, but `show --errors` lists its codes.
+pub fn books(doc_dir: &Path) -> Result<Vec<(String, PathBuf)>, Error> {
+    let mut books = Vec::new();
+    for entry in doc_dir.read_dir()? {
+        let entry = entry?;
+        let name = match entry.file_name().into_string() {
+            Ok(name) if name != "error_codes" => name,
+            _ => continue
+        };
+        if let Some(index) = entry
+            .path()
+            .is_dir()
+            .then(|| index_file(&entry.path()))
+            .flatten()
+        {
+            books.push((name, index));
+        }
+    }
+    books.sort();
+    Ok(books)
+}
+
+/// Sections of every book in the doc dir, a book per item like [`crate::read_search_index`]
+pub fn read_books(
+    doc_dir: &Path
+) -> Result<impl ParallelIterator<Item = Result<(String, Vec<Section>), Error>>, Error> {
+    Ok(books(doc_dir)?
+        .into_par_iter()
+        .map(|(name, index)| read_book(&name, &index).map(|sections| (name, sections))))
+}
+
+pub fn read_book(name: &str, index: &Path) -> Result<Vec<Section>, Error> {
+    let contents = std::fs::read_to_string(index)?;
+    let invalid = || Error::InvalidFormat(index.display().to_string());
+    // mdBook 0.4.41 wraps the object in `JSON.parse('..')`
+    let contents = match contents.contains("JSON.parse('") {
+        true => crate::js_string(&contents).ok_or_else(invalid)?,
+        false => contents
+    };
+    let start = contents.find('{').ok_or_else(invalid)?;
+    let json = serde_json::Deserializer::from_str(&contents[start..])
+        .into_iter::<Value>()
+        .next()
+        .ok_or_else(invalid)?
+        .map_err(|e| Error::SerdeJson(index.display().to_string(), e))?;
+    let urls = json
+        .get("doc_urls")
+        .and_then(Value::as_array)
+        .ok_or_else(invalid)?;
+    let docs = json.pointer("/index/documentStore/docs");
+    let mut chapters = HashSet::new();
+    let mut sections = Vec::new();
+    for (i, url) in urls.iter().enumerate() {
+        let url = match url.as_str() {
+            Some(url) => url,
+            None => continue
+        };
+        let (page, anchor) = match url.split_once('#') {
+            Some((page, anchor)) => (page, Some(anchor.to_owned())),
+            None => (url, None)
+        };
+        let chapter = std::iter::once(name)
+            .chain(page.trim_end_matches(".html").split('/'))
+            .collect::<Vec<_>>()
+            .join("::");
+        // The first heading stands for the chapter
+        let path = match &anchor {
+            Some(anchor) if !chapters.insert(chapter.clone()) => {
+                format!("{}::{}", chapter, anchor)
+            }
+            _ => chapter
+        };
+        let title = docs
+            .and_then(|d| d.get(i.to_string())?.get("title")?.as_str())
+            .unwrap_or_default();
+        sections.push(Section {
+            path,
+            title: title.into(),
+            file: Path::new(name).join(page),
+            anchor
+        });
+    }
+    Ok(sections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::DocTree;
+
+    const REFERENCE: (&str, &str) = (
+        "reference/searchindex.js",
+        r#"Object.assign(window.search, {"doc_urls":["introduction.html#introduction","items/traits.html#traits","items/traits.html#object-safety"],"index":{"documentStore":{"docs":{"0":{"body":"","breadcrumbs":"Introduction","id":"0","title":"Introduction"},"1":{"body":"","breadcrumbs":"Items » Traits","id":"1","title":"Traits"},"2":{"body":"","breadcrumbs":"Items » Traits » Object Safety","id":"2","title":"Object Safety"}},"length":3}},"results_options":{"limit_results":30}});"#
+    );
+
+    /// The error index, which is listed by `show --errors` instead
+    const ERROR_CODES: (&str, &str) = (
+        "error_codes/searchindex.json",
+        r#"{"doc_urls":["E0308.html#error-code-e0308"],"index":{"documentStore":{"docs":{}}}}"#
+    );
+
+    #[test]
+    fn sections_of_books() {
+        let tree = DocTree::new(&[REFERENCE, ERROR_CODES]);
+        let search_index = tree.search_index();
+        let doc_dir = tree.doc_dir();
+        let found = read_books(doc_dir)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        let (name, sections) = &found[0];
+        assert_eq!(name, "reference");
+        assert_eq!(
+            sections.iter().map(Section::line).collect::<Vec<_>>(),
+            vec![
+                "reference::introduction\tsection",
+                "reference::items::traits\tsection",
+                "reference::items::traits::object-safety\tsection"
+            ]
+        );
+        assert_eq!(sections[2].title, "Object Safety");
+        assert!(is_section("reference::items::traits\tsection"));
+        assert!(!is_section("deep::Choice\tenum"));
+        let mut resolver = crate::location::Resolver::for_test(search_index.clone());
+        let location = resolver
+            .location("reference::items::traits\tsection")
+            .unwrap();
+        assert_eq!(location.file, doc_dir.join("reference/items/traits.html"));
+        assert_eq!(location.anchor.as_deref(), Some("traits"));
+        let location = resolver
+            .location("reference::items::traits::object-safety\tsection")
+            .unwrap();
+        assert_eq!(location.file, doc_dir.join("reference/items/traits.html"));
+        assert_eq!(location.anchor.as_deref(), Some("object-safety"));
+        assert!(resolver
+            .location("reference::items::unions\tsection")
+            .is_err());
+        assert!(resolver.location("no-such-book::intro\tsection").is_err());
+    }
+}
@@ -9,6 +9,7 @@ pub struct Crate {
     // t, n, q, d, i, f are items array
     t: Vec<ItemType>,
     n: Vec<String>,
+    #[allow(dead_code)]
     f: F,
     q: Vec<String>,
     d: Vec<String>,
@@ -49,6 +50,7 @@ pub enum Types1_55_0 {
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct Type1_55_0 {
     name: String,
     generics: Option<Vec<String>>,
@@ -64,6 +66,7 @@ pub enum Types1_58_0 {
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct Type1_58_0 {
     name: String,
     generics: Option<Vec<Type1_58_0>>,
@@ -146,7 +149,7 @@ impl<'de> serde::Deserialize<'de> for Type1_58_0 {
     }
 }
 
-#[derive(Debug, serde_repr::Deserialize_repr)]
+#[derive(Debug, serde_repr::Deserialize_repr, PartialEq, Eq, Clone, Copy)]
 #[repr(u8)]
 pub enum ParentType {
     Struct = 3,
@@ -158,6 +161,20 @@ pub enum ParentType {
     Union = 19
 }
 
+impl From<ParentType> for ItemType {
+    fn from(p: ParentType) -> Self {
+        match p {
+            ParentType::Struct => ItemType::Struct,
+            ParentType::Enum => ItemType::Enum,
+            ParentType::Typedef => ItemType::Typedef,
+            ParentType::Trait => ItemType::Trait,
+            ParentType::Variant => ItemType::Variant,
+            ParentType::Primitive => ItemType::Primitive,
+            ParentType::Union => ItemType::Union
+        }
+    }
+}
+
 /// rust/src/librustdoc/formats/item_type.rs
 #[derive(Debug, serde_repr::Deserialize_repr, PartialEq, Eq, Clone, Copy)]
 #[repr(u8)]
@@ -311,26 +328,52 @@ impl FromStr for ItemType {
     }
 }
 
+/// An item of the search index with the parent resolved
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub ty: ItemType,
+    pub name: String,
+    /// Module path such as `std::collections`
+    pub path: String,
+    pub parent: Option<(ParentType, String)>,
+    /// The first line of the docs as HTML
+    pub desc: String
+}
+
+impl Item {
+    pub fn full_path(&self) -> String {
+        match &self.parent {
+            None => format!("{}::{}", self.path, self.name),
+            Some((_, parent)) => format!("{}::{}::{}", self.path, parent, self.name)
+        }
+    }
+
+    /// A line of list
+    pub fn line(&self) -> String { format!("{}\t{}", self.full_path(), self.ty.as_str()) }
+}
+
 impl Crate {
-    // TODO: duplicated methods
-    pub fn items(self) -> Vec<String> {
-        let Self { p, t, n, q, i, .. } = self;
-        let items = (0..)
-            .zip(t.into_iter())
-            .zip(n.into_iter())
-            .zip(i.into_iter())
-            .zip(q.into_iter())
-            .map(|((((no, t), n), i), q)| (no, t, n, i, q));
+    pub fn items(self) -> Vec<String> { self.entries().iter().map(Item::line).collect() }
+
+    pub fn entries(self) -> Vec<Item> {
+        let Self {
+            p, t, n, q, d, i, ..
+        } = self;
+        // Never drop items even if descriptions run short
+        let d = d.into_iter().chain(std::iter::repeat_with(String::new));
+        let items = t.into_iter().zip(n).zip(i).zip(q).zip(d);
         let mut cd: String = String::new();
         items
-            .map(|(_no, t, n, i, q)| {
+            .map(|((((t, n), i), q), d)| {
                 if !q.is_empty() {
                     cd = q;
                 }
-                if i == 0 {
-                    format!("{}::{}	{}", &cd, n, t.as_str())
-                } else {
-                    format!("{}::{}::{}	{}", &cd, p[i - 1].1, n, t.as_str())
+                Item {
+                    ty: t,
+                    name: n,
+                    path: cd.clone(),
+                    parent: i.checked_sub(1).map(|i| p[i].clone()),
+                    desc: d
                 }
             })
             .collect()
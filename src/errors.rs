@@ -0,0 +1,231 @@
+//! Explanations of compiler error codes from the error index of rust-docs
+//!
+//! Since rust 1.67 the index is an mdBook with a page per code in `error_codes/`, and before it
+//! a single `error-index.html` with a section per code.
+use crate::{
+    html::{self, Token},
+    location::LocationError,
+    view, Error
+};
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// The kind of error code lines, in place of an item type
+pub const ERROR: &str = "error";
+
+/// The single page of older rust-docs
+pub const OLD_INDEX: &str = "error-index.html";
+
+/// A code and where it is explained
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorCode {
+    /// `E0308`
+    pub code: String,
+    /// The first sentence of the explanation
+    pub summary: String,
+    pub file: PathBuf,
+    pub anchor: Option<String>
+}
+
+impl ErrorCode {
+    pub fn line(&self) -> String { format!("{}\t{}", self.code, ERROR) }
+}
+
+/// Whether the line of list is of an error code
+pub fn is_error(line: &str) -> bool { line.split_whitespace().nth(1) == Some(ERROR) }
+
+/// `E0308` of `E0308`, `e308`, `308` or a line of list
+pub fn normalize(code: &str) -> Option<String> {
+    let code = code.split_whitespace().next()?;
+    let digits = code.strip_prefix(['E', 'e']).unwrap_or(code);
+    (!digits.is_empty() && digits.len() <= 4 && digits.bytes().all(|b| b.is_ascii_digit()))
+        .then(|| format!("E{:0>4}", digits))
+}
+
+/// Whether it is a code as written in the index, such as the ids of [`OLD_INDEX`]
+pub fn is_code(s: &str) -> bool { normalize(s).as_deref() == Some(s) }
+
+/// Whether the doc dir has the error index
+pub fn has_index(doc_dir: &Path) -> bool {
+    doc_dir.join("error_codes").is_dir() || doc_dir.join(OLD_INDEX).is_file()
+}
+
+/// Every code of the doc dir in order
+pub fn error_codes(doc_dir: &Path) -> Result<Vec<ErrorCode>, Error> {
+    let dir = doc_dir.join("error_codes");
+    if dir.is_dir() {
+        let mut codes = dir
+            .read_dir()?
+            .filter_map(|e| e.ok()?.file_name().into_string().ok())
+            .filter_map(|name| Some(name.strip_suffix(".html")?.to_owned()))
+            .filter(|code| is_code(code))
+            .collect::<Vec<_>>();
+        codes.sort();
+        return codes
+            .into_par_iter()
+            .map(|code| {
+                let file = dir.join(format!("{}.html", code));
+                let contents = std::fs::read_to_string(&file)?;
+                Ok(ErrorCode {
+                    summary: view::docblock(&contents, None)
+                        .map(|e| summary(&e))
+                        .unwrap_or_default(),
+                    code,
+                    file,
+                    anchor: None
+                })
+            })
+            .collect();
+    }
+    let file = doc_dir.join(OLD_INDEX);
+    if !file.is_file() {
+        return Ok(Vec::new());
+    }
+    let contents = std::fs::read_to_string(&file)?;
+    let ids = contents
+        .split(r#"id=""#)
+        .skip(1)
+        .filter_map(|s| Some(&s[..s.find('"')?]))
+        .filter(|id| is_code(id));
+    Ok(ids
+        .map(|code| ErrorCode {
+            code: code.into(),
+            summary: view::docblock(&contents, Some(code))
+                .map(|e| summary(&e))
+                .unwrap_or_default(),
+            file: file.clone(),
+            anchor: Some(code.into())
+        })
+        .collect())
+}
+
+/// The page and the anchor of the code, such as `E0308` or a line of list
+pub fn find(doc_dir: &Path, code: &str) -> Result<(PathBuf, Option<String>), Error> {
+    let code = normalize(code).ok_or(LocationError::InvalidFormat)?;
+    let page = doc_dir.join("error_codes").join(format!("{}.html", code));
+    if page.is_file() {
+        return Ok((page, None));
+    }
+    let old = doc_dir.join(OLD_INDEX);
+    if old.is_file() && std::fs::read_to_string(&old)?.contains(&format!(r#"id="{}""#, code)) {
+        return Ok((old, Some(code)));
+    }
+    Err(LocationError::ItemNotFound.into())
+}
+
+/// The first sentence on one line, marking codes that the compiler no longer emits
+fn summary(explanation: &str) -> String {
+    let first = html::tokens(explanation)
+        .find_map(|token| match token {
+            Token::Start { name: "p", pos, .. } => html::element_at(explanation, pos),
+            _ => None
+        })
+        .map(html::text)
+        .unwrap_or_default();
+    let first = first_sentence(&crate::signature::one_line(&first)).to_owned();
+    if explanation.contains("no longer emitted") {
+        format!("(no longer emitted) {}", first)
+    } else {
+        first
+    }
+}
+
+/// Up to a period followed by a capital letter, so that `e.g. a` does not end it
+fn first_sentence(paragraph: &str) -> &str {
+    paragraph
+        .match_indices(". ")
+        .find(|(i, m)| paragraph[i + m.len()..].starts_with(|c: char| c.is_uppercase()))
+        .map_or(paragraph, |(i, _)| &paragraph[..=i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::DocTree;
+
+    /// The single page of rust-docs 1.60
+    const OLD: (&str, &str) = (
+        "error-index.html",
+        r##"<h1>Rust Compiler Error Index</h1>
+<div class="error-described error-unused"><h2 id="E0001" class="section-header"><a href="#E0001">E0001</a></h2>
+<h4>Note: this error code is no longer emitted by the compiler.</h4>
+<p>This error suggests that the arm will never be reached. Patterns are matched in order, e.g. from top to bottom.</p>
+</div>
+<div class="error-described error-used"><h2 id="E0308" class="section-header"><a href="#E0308">E0308</a></h2>
+<p>Expected type did not match the received type.</p>
+<pre class="rust rust-example-rendered"><code>plus_one(&quot;Not a number&quot;);</code></pre>
+</div>"##
+    );
+
+    /// A page of the mdBook since rust 1.67
+    const E0308: (&str, &str) = (
+        "error_codes/E0308.html",
+        r#"<!-- Book generated using mdBook --><h2>Keyboard shortcuts</h2><main><h1 id="error-code-e0308">Error code E0308</h1>
+<p>Expected type did not
+match the received type.</p></main><nav><p>Next</p></nav>"#
+    );
+
+    #[test]
+    fn codes_of_error_index() {
+        let tree = DocTree::new(&[OLD]);
+        let doc_dir = tree.doc_dir();
+        let old = doc_dir.join(OLD_INDEX);
+        assert!(has_index(doc_dir));
+        assert!(!has_index(crate::fixture::doc_tree().doc_dir()));
+        assert_eq!(
+            error_codes(doc_dir).unwrap(),
+            vec![
+                ErrorCode {
+                    code: "E0001".into(),
+                    summary: "(no longer emitted) This error suggests that the arm will never be reached.".into(),
+                    file: old.clone(),
+                    anchor: Some("E0001".into())
+                },
+                ErrorCode {
+                    code: "E0308".into(),
+                    summary: "Expected type did not match the received type.".into(),
+                    file: old.clone(),
+                    anchor: Some("E0308".into())
+                }
+            ]
+        );
+        assert_eq!(
+            find(doc_dir, "e308\terror").unwrap(),
+            (old.clone(), Some("E0308".into()))
+        );
+        assert!(find(doc_dir, "E0999").is_err());
+        assert!(find(doc_dir, "E03080").is_err());
+        let newer = DocTree::new(&[E0308]);
+        let pages = newer.doc_dir().join("error_codes");
+        let codes = error_codes(newer.doc_dir()).unwrap();
+        assert_eq!(codes.len(), 1);
+        assert_eq!(codes[0].line(), "E0308\terror");
+        assert_eq!(
+            codes[0].summary,
+            "Expected type did not match the received type."
+        );
+        assert_eq!(
+            find(newer.doc_dir(), "0308").unwrap(),
+            (pages.join("E0308.html"), None)
+        );
+        let mut resolver = crate::location::Resolver::for_test(newer.search_index());
+        let location = resolver.location("E0308\terror").unwrap();
+        let text = crate::view::view(
+            &location,
+            crate::view::Style {
+                width: 80,
+                color: false
+            }
+        )
+        .unwrap();
+        assert_eq!(
+            text,
+            "Error code E0308\n\nExpected type did not match the received type.\n"
+        );
+        assert!(!is_error("std::error::Error\ttrait"));
+        assert_eq!(
+            first_sentence("A value, e.g. `x`, is moved. It was used before."),
+            "A value, e.g. `x`, is moved."
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Code examples in docs and scratch projects to run them
+use crate::{
+    html,
+    workspace::{SourceKind, Workspace}
+};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// A Rust code block of docs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub code: String,
+    /// Attributes of the code block such as `should_panic` or `compile_fail`
+    pub attrs: Vec<String>,
+    /// Whether hidden `#` lines are restored from the playground link
+    pub complete: bool
+}
+
+impl Example {
+    /// The code with `fn main` as rustdoc adds it
+    pub fn program(&self) -> String {
+        if self.complete || self.code.contains("fn main") {
+            format!("{}\n", self.code)
+        } else {
+            format!("fn main() {{\n{}\n}}\n", self.code)
+        }
+    }
+}
+
+/// Rust code blocks of docs, not declarations
+pub fn examples(fragment: &str) -> Vec<Example> {
+    fragment
+        .match_indices("rust-example-rendered")
+        .filter_map(|(i, _)| {
+            let start = fragment[..i].rfind('<')?;
+            if !fragment[start..].starts_with("<pre") {
+                return None;
+            }
+            let pre = html::element_at(fragment, start)?;
+            let class = html::attr(&pre[..pre.find('>')?], "class").unwrap_or_default();
+            let attrs = class
+                .split(' ')
+                .filter(|c| !matches!(*c, "" | "rust" | "rust-example-rendered"))
+                .map(str::to_owned)
+                .collect();
+            // The playground link follows the code in its wrapper
+            let wrap = fragment[..start]
+                .rfind(r#"<div class="example-wrap"#)
+                .and_then(|w| Some((w, html::element_at(fragment, w)?)))
+                .filter(|(w, e)| w + e.len() > start);
+            let full = wrap.and_then(|(w, e)| playground_code(e, start - w));
+            Some(Example {
+                complete: full.is_some(),
+                code: full.unwrap_or_else(|| html::text(pre).trim_end().to_owned()),
+                attrs
+            })
+        })
+        .collect()
+}
+
+/// The code of the Run link after the block, which keeps hidden lines
+fn playground_code(wrap: &str, pre: usize) -> Option<String> {
+    let rest = &wrap[pre..];
+    let arrow = rest.find(r#"class="test-arrow""#)?;
+    let tag_start = rest[..arrow].rfind('<')?;
+    let tag = &rest[tag_start..tag_start + rest[tag_start..].find('>')?];
+    let href = html::unescape(html::attr(tag, "href")?);
+    let url = Url::parse(&href).ok()?;
+    let code = url.query_pairs().find(|(k, _)| k == "code")?.1;
+    Some(code.trim_end().to_owned())
+}
+
+/// The dependency on the crate of the item for `Cargo.toml`
+///
+/// Crates from registries are pinned to the locked version, and others are depended on by path.
+pub fn dependency(krate_name: &str, workspace: &Workspace) -> Option<String> {
+    let info = workspace.info(krate_name)?;
+    let source = match info.source {
+        SourceKind::Registry => format!(r#"version = "={}""#, info.version),
+        SourceKind::Git | SourceKind::Path => format!(
+            "path = {}",
+            toml_string(&info.manifest_path.parent()?.to_string_lossy())
+        )
+    };
+    Some(format!(
+        "{} = {{ package = {}, {} }}",
+        krate_name,
+        toml_string(&info.name),
+        source
+    ))
+}
+
+fn toml_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Writes a cargo project with a binary per example, skipping `compile_fail` ones
+///
+/// Refuses a directory that already has a `Cargo.toml` or `src` so as not to overwrite a project.
+pub fn scaffold(
+    dir: &Path,
+    name: &str,
+    dependency: Option<&str>,
+    examples: &[Example]
+) -> std::io::Result<Vec<PathBuf>> {
+    if let Some(existing) = ["Cargo.toml", "src"]
+        .iter()
+        .map(|f| dir.join(f))
+        .find(|f| f.exists())
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{} already exists", existing.display())
+        ));
+    }
+    let bin_dir = dir.join("src").join("bin");
+    std::fs::create_dir_all(&bin_dir)?;
+    let mut manifest = format!(
+        "[package]\nname = {}\nversion = \"0.0.0\"\nedition = \"2021\"\npublish = false\n\n[dependencies]\n",
+        toml_string(name)
+    );
+    if let Some(dependency) = dependency {
+        manifest.push_str(dependency);
+        manifest.push('\n');
+    }
+    // Stay out of a workspace that contains the directory
+    manifest.push_str("\n[workspace]\n");
+    std::fs::write(dir.join("Cargo.toml"), manifest)?;
+    let mut written = Vec::new();
+    for (i, example) in examples.iter().enumerate() {
+        if example.attrs.iter().any(|a| a == "compile_fail") {
+            continue;
+        }
+        let file = bin_dir.join(format!("example{}.rs", i + 1));
+        std::fs::write(&file, example.program())?;
+        written.push(file);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn examples_of_docs() {
+        let tree = crate::fixture::doc_tree();
+        let doc_dir = tree.doc_dir();
+        let contents =
+            std::fs::read_to_string(doc_dir.join("deep/outer/inner/trait.Shape.html")).unwrap();
+        let fragment = crate::view::docblock(&contents, Some("tymethod.area")).unwrap();
+        let found = examples(&fragment);
+        assert_eq!(
+            found,
+            vec![
+                Example {
+                    code: "#![allow(unused)]\nfn main() {\nuse deep::Shape;\nlet a = w.area();\n}"
+                        .into(),
+                    attrs: vec![],
+                    complete: true
+                },
+                Example {
+                    code: "area();".into(),
+                    attrs: vec!["compile_fail".into()],
+                    complete: false
+                }
+            ]
+        );
+        let dir = doc_dir.join("scratch");
+        let written = scaffold(
+            &dir,
+            "area-examples",
+            Some(r#"deep = { path = "/deep" }"#),
+            &found
+        )
+        .unwrap();
+        assert_eq!(written, vec![dir.join("src/bin/example1.rs")]);
+        let manifest = std::fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+        assert!(manifest.contains("[dependencies]\ndeep = { path = \"/deep\" }\n"));
+        let err = scaffold(&dir, "area-examples", None, &found).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("Cargo.toml")).unwrap(),
+            manifest
+        );
+        let contents =
+            std::fs::read_to_string(doc_dir.join("deep/outer/inner/struct.Widget.html")).unwrap();
+        let fragment = crate::view::docblock(&contents, None).unwrap();
+        let found = examples(&fragment);
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].program(),
+            "fn main() {\nlet w = Widget::new();\n}\n"
+        );
+    }
+}
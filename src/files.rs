@@ -0,0 +1,137 @@
+//! Source files of documented crates from `source-files.js` or `src-files.js`
+use crate::Error;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// A source file and its rendered page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    pub krate: String,
+    /// The path relative to the directory of the crate root
+    pub path: PathBuf,
+    /// `<doc dir>/src/<crate>/<path>.html`
+    pub page: PathBuf
+}
+
+/// Source files of every crate in the doc dir, in the order of the file
+pub fn source_files(doc_dir: &Path) -> Result<Vec<SourceFile>, Error> {
+    let names = doc_dir
+        .read_dir()?
+        .filter_map(|e| e.ok()?.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".js"))
+        .collect::<Vec<_>>();
+    // Renamed in rustdoc 1.74, and rust-docs has the resource suffix as in `src-files1.75.0.js`
+    let file = match ["src-files", "source-files"]
+        .iter()
+        .find_map(|prefix| names.iter().find(|name| name.starts_with(prefix)))
+    {
+        Some(name) => doc_dir.join(name),
+        None => return Ok(Vec::new())
+    };
+    let js = std::fs::read_to_string(&file)?;
+    let invalid = || Error::InvalidFormat(file.display().to_string());
+    let json = crate::js_string(&js).ok_or_else(invalid)?;
+    let tree: Value =
+        serde_json::from_str(&json).map_err(|e| Error::SerdeJson(file.display().to_string(), e))?;
+    // `{"crate":{..}}` of 1.60, or `[["crate",[..]]]` for a `Map` later
+    let krates: Vec<(String, &Value)> = match &tree {
+        Value::Object(o) => o.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Value::Array(a) => a
+            .iter()
+            .filter_map(|pair| Some((pair.get(0)?.as_str()?.to_owned(), pair.get(1)?)))
+            .collect(),
+        _ => return Err(invalid())
+    };
+    let mut files = Vec::new();
+    for (krate, root) in krates {
+        let mut paths = Vec::new();
+        walk(root, PathBuf::new(), &mut paths);
+        let src_dir = doc_dir.join("src").join(&krate);
+        files.extend(paths.into_iter().map(|path| {
+            let mut page = src_dir.join(&path).into_os_string();
+            page.push(".html");
+            SourceFile {
+                krate: krate.clone(),
+                path,
+                page: page.into()
+            }
+        }));
+    }
+    Ok(files)
+}
+
+/// `{"name":..,"dirs":[..],"files":[..]}` of 1.60, or `[name, dirs, files]` with empty lists
+/// omitted at the end
+fn walk(node: &Value, dir: PathBuf, out: &mut Vec<PathBuf>) {
+    let (dirs, files) = match node {
+        Value::Object(o) => (o.get("dirs"), o.get("files")),
+        Value::Array(a) => (a.get(1), a.get(2)),
+        _ => return
+    };
+    let name = |v: &Value| match v {
+        Value::Object(o) => o.get("name").and_then(Value::as_str).map(str::to_owned),
+        Value::Array(a) => a.first().and_then(Value::as_str).map(str::to_owned),
+        _ => None
+    };
+    for file in files.and_then(Value::as_array).into_iter().flatten() {
+        out.extend(file.as_str().map(|f| dir.join(f)));
+    }
+    for sub in dirs.and_then(Value::as_array).into_iter().flatten() {
+        if let Some(n) = name(sub) {
+            walk(sub, dir.join(n), out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::DocTree;
+
+    const SOURCE_FILES: (&str, &str) = (
+        "source-files.js",
+        r#"var sourcesIndex = JSON.parse('{\
+"deep":{"name":"","dirs":[{"name":"outer","files":["mod.rs"]}],"files":["lib.rs"]},\
+"other":{"name":"","files":["lib.rs"]}\
+}');
+createSourceSidebar();"#
+    );
+
+    const SRC_FILES: (&str, &str) = (
+        "src-files.js",
+        r#"createSrcSidebar('[["deep",["",[["outer",[],["mod.rs"]]],["lib.rs"]]],["other",["",[],["lib.rs"]]]]');"#
+    );
+
+    #[test]
+    fn files_of_doc_dir() {
+        let expected = |doc_dir: &Path| {
+            let file = |krate: &str, path: &str| SourceFile {
+                krate: krate.into(),
+                path: path.into(),
+                page: doc_dir
+                    .join("src")
+                    .join(krate)
+                    .join(format!("{}.html", path))
+            };
+            vec![
+                file("deep", "lib.rs"),
+                file("deep", "outer/mod.rs"),
+                file("other", "lib.rs"),
+            ]
+        };
+        let tree = DocTree::new(&[SOURCE_FILES]);
+        let doc_dir = tree.doc_dir();
+        assert_eq!(source_files(doc_dir).unwrap(), expected(doc_dir));
+        let newer = DocTree::new(&[SRC_FILES]);
+        assert_eq!(
+            source_files(newer.doc_dir()).unwrap(),
+            expected(newer.doc_dir())
+        );
+        let suffixed = DocTree::new(&[("src-files1.75.0.js", SRC_FILES.1)]);
+        assert_eq!(
+            source_files(suffixed.doc_dir()).unwrap(),
+            expected(suffixed.doc_dir())
+        );
+        assert!(source_files(&doc_dir.join("deep")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+//! A small doc dir in the format of rustdoc 1.60
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering}
+};
+
+const SEARCH_INDEX_FILE: &str = "search-index1.60.0.js";
+
+const SEARCH_INDEX: &str = r#"var searchIndex = JSON.parse('{\
+"deep":{"doc":"","t":[0,0,3,11,11,11,8,16,10,4,13,13,12,12,5,2],"n":["outer","inner","Widget","new","fmt","fmt","Shape","Output","area","Choice","A","B","x","0","free","Shape"],"q":["deep","deep::outer","deep::outer::inner","","","","","","","deep","","","deep::Choice","","deep::outer","deep"],"d":["Outer module","","A widget","Creates a widget","","","A shape","","Computes the area","A re-exported enum","","","","","A free function",""],"i":[0,0,0,1,1,1,0,2,2,0,3,3,4,5,0,0],"f":[null,null,null,null,null,null,null,null,null,null,null,null,null,null,null,null],"p":[[3,"Widget"],[8,"Shape"],[4,"Choice"],[13,"A"],[13,"B"]]},\
+"other":{"doc":"","t":[5,3],"n":["f","Widget"],"q":["other",""],"d":["",""],"i":[0,0],"f":[null,null],"p":[]}\
+}');
+if (window.initSearch) {window.initSearch(searchIndex)};
+"#;
+
+const FILES: &[(&str, &str)] = &[
+    (
+        "deep/index.html",
+        r#"<h1>Crate deep</h1>
+<div class="item-left import-item" id="reexport.Shape"><code>pub use outer::inner::<a class="trait" href="outer/inner/trait.Shape.html" title="trait deep::outer::inner::Shape">Shape</a>;</code></div>"#
+    ),
+    (
+        "deep/outer/struct.Widget.html",
+        r#"<!DOCTYPE html><html lang="en"><head><meta http-equiv="refresh" content="0;URL=../../deep/outer/inner/struct.Widget.html"><title>Redirection</title></head><body><p>Redirecting to <a href="../../deep/outer/inner/struct.Widget.html">../../deep/outer/inner/struct.Widget.html</a>...</p></body></html>"#
+    ),
+    ("deep/outer/index.html", r#"<h1>Module outer</h1>"#),
+    ("deep/outer/inner/index.html", r#"<h1>Module inner</h1>"#),
+    (
+        "deep/outer/inner/struct.Widget.html",
+        r##"<h1 class="fqn"><span class="in-band">Struct <a class="struct" href="#">Widget</a><button id="copy-path">Copy item path</button></span><span class="out-of-band"><a class="srclink" href="../../../src/deep/lib.rs.html#1">source</a></span></h1>
+<div class="docblock item-decl"><pre class="rust struct"><code>pub struct Widget</code></pre></div>
+<details class="rustdoc-toggle top-doc" open><summary class="hideme"><span>Expand description</span></summary><div class="docblock"><p>A widget that is drawn on the screen.</p>
+<div class="example-wrap"><pre class="rust rust-example-rendered"><code><span class="kw">let</span> w = Widget::new();</code></pre></div></div></details>
+<h2 id="implementations" class="small-section-header">Implementations</h2>
+<div id="impl" class="impl has-srclink"><h3 class="code-header">impl Widget</h3></div>
+<details class="rustdoc-toggle method-toggle" open><summary><section id="method.new" class="method"><a class="srclink rightside" href="../../../src/deep/lib.rs.html#3-6">source</a><h4 class="code-header">pub fn <a href="#method.new" class="fnname">new</a>() -&gt; Widget</h4></section></summary><div class="docblock"><p>Creates a widget like <a href="../../enum.Choice.html#variant.A" title="variant deep::Choice::A"><code>Choice::A</code></a></p></div></details>
+<div id="trait-implementations-list">
+<div id="impl-Display-for-Widget" class="impl"><h3 class="code-header">impl Display for Widget</h3></div>
+<section id="method.fmt" class="method trait-impl"><h4 class="code-header">fn fmt(&amp;self, f: &amp;mut Formatter&lt;'_&gt;) -&gt; Result</h4></section>
+<div id="impl-Debug-for-Widget" class="impl"><h3 class="code-header">impl Debug for Widget</h3></div>
+<section id="method.fmt-1" class="method trait-impl"><h4 class="code-header">fn fmt(&amp;self, f: &amp;mut Formatter&lt;'_&gt;) -&gt; Result</h4></section>
+</div>
+<h2 id="synthetic-implementations" class="small-section-header">Auto Trait Implementations</h2>
+<div id="synthetic-implementations-list"><div id="impl-Send-for-Widget" class="impl"><h3 class="code-header">impl Send for Widget</h3></div></div>
+<h2 id="blanket-implementations" class="small-section-header">Blanket Implementations</h2>
+<div id="blanket-implementations-list"><div id="impl-From%3CT%3E-for-T" class="impl"><h3 class="code-header">impl&lt;T&gt; From&lt;T&gt; for T</h3></div></div>"##
+    ),
+    (
+        "deep/outer/inner/trait.Shape.html",
+        r#"<h1>Trait Shape</h1>
+<section id="associatedtype.Output" class="method"><h4 class="code-header">type Output</h4></section>
+<section id="tymethod.area" class="method"><h4 class="code-header">fn area(&amp;self) -> f64</h4></section>
+<span class="item-info"><div class="stab deprecated"><span class="emoji">👎</span><span>Deprecated since 0.2.0: use perimeter</span></div></span><div class="docblock"><p>Computes the area</p>
+<div class="example-wrap"><pre class="rust rust-example-rendered"><code><span class="kw">let</span> a = w.area();</code></pre><a class="test-arrow" target="_blank" href="https://play.rust-lang.org/?code=%23!%5Ballow(unused)%5D%0Afn%20main()%20%7B%0Ause%20deep%3A%3AShape%3B%0Alet%20a%20%3D%20w.area()%3B%0A%7D&amp;edition=2021">Run</a></div>
+<div class="example-wrap compile_fail"><div class="tooltip">ⓘ</div><pre class="rust rust-example-rendered compile_fail"><code>area();</code></pre></div></div>
+<h2 id="implementors" class="small-section-header">Implementors</h2>
+<div id="implementors-list"><div id="impl-Shape-for-Widget" class="impl"><h3 class="code-header">impl Shape for Widget</h3></div></div>"#
+    ),
+    (
+        "deep/enum.Choice.html",
+        r#"<h1>Enum Choice</h1>
+<section id="variant.A" class="variant"><h3 class="code-header">A</h3></section>
+<span id="variant.A.field.x" class="section-header">x: u8</span>
+<section id="variant.B" class="variant"><h3 class="code-header">B(u8)</h3></section>"#
+    ),
+    ("deep/outer/fn.free.html", r#"<h1>Function free</h1>"#),
+    ("other/fn.f.html", r#"<h1>Function f</h1>"#),
+    ("other/struct.Widget.html", r#"<h1>Struct Widget</h1>"#),
+    (
+        "implementors/deep/outer/inner/trait.Shape.js",
+        r#"(function() {var implementors = {};
+implementors["deep"] = [{"text":"impl <a class=\"trait\" href=\"deep/outer/inner/trait.Shape.html\" title=\"trait deep::outer::inner::Shape\">Shape</a> for <a class=\"struct\" href=\"deep/outer/inner/struct.Widget.html\" title=\"struct deep::outer::inner::Widget\">Widget</a>","synthetic":false,"types":["deep::outer::inner::Widget"]}];
+implementors["other"] = [{"text":"impl&lt;T: <a class=\"trait\" href=\"deep/outer/inner/trait.Shape.html\" title=\"trait deep::outer::inner::Shape\">Shape</a>&gt; <a class=\"trait\" href=\"deep/outer/inner/trait.Shape.html\" title=\"trait deep::outer::inner::Shape\">Shape</a> for <a class=\"struct\" href=\"https://doc.rust-lang.org/1.60.0/alloc/boxed/struct.Box.html\" title=\"struct alloc::boxed::Box\">Box</a>&lt;T&gt;","synthetic":false,"types":["alloc::boxed::Box"]}];
+if (window.register_implementors) {window.register_implementors(implementors);} else {window.pending_implementors = implementors;}})()"#
+    ),
+    (
+        "src/deep/lib.rs.html",
+        r#"<div class="example-wrap"><pre class="line-numbers"><span id="1">1</span>
+<span id="2">2</span>
+<span id="3">3</span>
+<span id="4">4</span>
+<span id="5">5</span>
+<span id="6">6</span>
+<span id="7">7</span>
+</pre><pre class="rust"><code><span class="kw">pub</span> <span class="kw">struct</span> <span class="ident">Widget</span>;
+<span class="kw">impl</span> <span class="ident">Widget</span> {
+    <span class="doccomment">/// Creates a widget like [`Choice::A`]</span>
+    <span class="kw">pub</span> <span class="kw">fn</span> <span class="ident">new</span>() -&gt; <span class="ident">Widget</span> {
+        <span class="ident">Widget</span>
+    }
+}
+</code></pre></div>"#
+    )
+];
+
+/// A doc dir in a new temporary directory, removed on drop
+pub struct DocTree {
+    root: PathBuf
+}
+
+impl DocTree {
+    /// The doc dir of rustdoc 1.60 with `extra` files, such as data of other rustdoc versions
+    pub fn new(extra: &[(&str, &str)]) -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "rustdoc-index-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        for (path, contents) in FILES.iter().chain(extra) {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        fs::write(root.join(SEARCH_INDEX_FILE), SEARCH_INDEX).unwrap();
+        Self { root }
+    }
+
+    pub fn doc_dir(&self) -> &Path { &self.root }
+
+    pub fn search_index(&self) -> PathBuf { self.root.join(SEARCH_INDEX_FILE) }
+}
+
+impl Drop for DocTree {
+    fn drop(&mut self) { fs::remove_dir_all(&self.root).ok(); }
+}
+
+pub fn doc_tree() -> DocTree { DocTree::new(&[]) }
@@ -0,0 +1,281 @@
+//! Just enough HTML scanning for pages generated by rustdoc
+
+/// The target of `<meta http-equiv="refresh" content="0;URL=...">` in redirect stubs
+pub fn redirect(contents: &str) -> Option<&str> {
+    let meta = &contents[contents.find(r#"http-equiv="refresh""#)?..];
+    let url = &meta[meta.find("URL=")? + 4..];
+    Some(&url[..url.find('"')?])
+}
+
+/// The whole element which has the id, including the start and end tags
+pub fn element_by_id<'a>(contents: &'a str, id: &str) -> Option<&'a str> {
+    let attr = format!(r#"id="{}""#, id);
+    let pos = contents.find(&attr)?;
+    let start = contents[..pos].rfind('<')?;
+    element_at(contents, start)
+}
+
+/// The element whose start tag begins at `start`
+pub fn element_at(contents: &str, start: usize) -> Option<&str> {
+    let rest = &contents[start..];
+    let name_len = rest[1..].find(|c: char| !c.is_ascii_alphanumeric())?;
+    let name = &rest[1..1 + name_len];
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let mut depth = 0;
+    let mut i = 0;
+    while i < rest.len() {
+        if rest[i..].starts_with(&close) {
+            depth -= 1;
+            if depth == 0 {
+                return Some(&rest[..i + close.len()]);
+            }
+            i += close.len();
+        } else if rest[i..].starts_with(&open)
+            && rest[i + open.len()..].starts_with(|c: char| c == '>' || c.is_whitespace())
+        {
+            depth += 1;
+            i += open.len();
+        } else {
+            i += rest[i..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    None
+}
+
+/// The heading with the id and what follows it up to a heading of the same or a higher level,
+/// as in pages of mdBook where sections are not elements
+pub fn section<'a>(contents: &'a str, id: &str) -> Option<&'a str> {
+    let heading = element_by_id(contents, id)?;
+    let level = |tag: &str| tag.strip_prefix("<h")?.chars().next()?.to_digit(10);
+    let max = level(heading)?;
+    let start = heading.as_ptr() as usize - contents.as_ptr() as usize;
+    let rest = &contents[start + heading.len()..];
+    let next = rest
+        .match_indices("<h")
+        .map(|(i, _)| i)
+        .find(|&i| level(&rest[i..]).is_some_and(|l| l <= max));
+    // Navigation follows the content of mdBook pages
+    let end = next
+        .into_iter()
+        .chain(rest.find("</main>"))
+        .min()
+        .unwrap_or(rest.len());
+    Some(&contents[start..start + heading.len() + end])
+}
+
+/// Values of `href` attributes in document order
+pub fn hrefs(fragment: &str) -> Vec<&str> {
+    fragment
+        .split(r#"href=""#)
+        .skip(1)
+        .filter_map(|s| s.find('"').map(|end| &s[..end]))
+        .collect()
+}
+
+/// A piece of HTML as written, without entities decoded
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Token<'a> {
+    /// A start tag beginning at `pos`, where `attrs` is the rest of the tag
+    Start {
+        name: &'a str,
+        attrs: &'a str,
+        pos: usize
+    },
+    End(&'a str),
+    Text(&'a str)
+}
+
+/// Tags, skipping comments and doctypes
+pub fn tokens(fragment: &str) -> impl Iterator<Item = Token<'_>> {
+    let mut i = 0;
+    std::iter::from_fn(move || loop {
+        let rest = &fragment[i..];
+        if rest.is_empty() {
+            return None;
+        }
+        if !rest.starts_with('<') {
+            let len = rest.find('<').unwrap_or(rest.len());
+            i += len;
+            return Some(Token::Text(&rest[..len]));
+        }
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            i += 4 + comment.find("-->").map_or(comment.len(), |e| e + 3);
+            continue;
+        }
+        let len = rest.find('>').map_or(rest.len(), |e| e + 1);
+        let pos = i;
+        i += len;
+        let tag = rest[1..len].trim_end_matches('>').trim_end_matches('/');
+        if let Some(name) = tag.strip_prefix('/') {
+            return Some(Token::End(name.trim()));
+        }
+        if tag.starts_with('!') || tag.starts_with('?') {
+            continue;
+        }
+        let name_len = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+        return Some(Token::Start {
+            name: &tag[..name_len],
+            attrs: &tag[name_len..],
+            pos
+        });
+    })
+}
+
+/// Classes of elements that are buttons and links around the docs
+const CHROME: &[&str] = &[
+    "out-of-band",
+    "rightside",
+    "srclink",
+    "anchor",
+    "hideme",
+    "notable-traits",
+    "tooltip",
+    "emoji",
+    "test-arrow",
+    // Hidden lines of examples in mdBook
+    "boring"
+];
+
+/// Tokens of the docs, without buttons, source links and other chrome
+pub fn content_tokens(fragment: &str) -> impl Iterator<Item = Token<'_>> {
+    let mut skip_until = 0;
+    tokens(fragment).filter(move |token| {
+        let pos = match *token {
+            Token::Start { pos, .. } => pos,
+            Token::End(s) | Token::Text(s) => s.as_ptr() as usize - fragment.as_ptr() as usize
+        };
+        if pos < skip_until {
+            return false;
+        }
+        if let Token::Start { name, attrs, pos } = *token {
+            let class = attr(attrs, "class").unwrap_or_default();
+            // Line numbers of source pages are marked `data-nosnippet`
+            if matches!(name, "button" | "script" | "style")
+                || class.split(' ').any(|c| CHROME.contains(&c))
+                || attrs.contains("data-nosnippet")
+            {
+                skip_until = element_at(fragment, pos).map_or(fragment.len(), |e| pos + e.len());
+                return false;
+            }
+        }
+        true
+    })
+}
+
+/// The text of the docs without tags
+pub fn text(fragment: &str) -> String {
+    content_tokens(fragment)
+        .filter_map(|t| match t {
+            Token::Text(s) => Some(unescape(s)),
+            _ => None
+        })
+        .collect()
+}
+
+/// Elements without an end tag
+pub fn is_void(name: &str) -> bool {
+    matches!(
+        name,
+        "area" | "base" | "br" | "col" | "embed" | "hr" | "img" | "input" | "link" | "meta" | "wbr"
+    )
+}
+
+/// The value of a double-quoted attribute
+pub fn attr<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let key = format!(r#"{}=""#, name);
+    let start = attrs
+        .match_indices(&key)
+        .map(|(i, _)| i)
+        .find(|&i| i == 0 || attrs[..i].ends_with(char::is_whitespace))?
+        + key.len();
+    let len = attrs[start..].find('"')?;
+    Some(&attrs[start..start + len])
+}
+
+/// Decodes character references in text
+pub fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&e| e <= 10).and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => '\u{a0}',
+                r => {
+                    let n = r.strip_prefix('#')?;
+                    let code = match n.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => n.parse().ok()?
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan() {
+        let stub = r#"<html><head><meta http-equiv="refresh" content="0;URL=../../deep/struct.Foo.html"><title>Redirection</title></head></html>"#;
+        assert_eq!(redirect(stub), Some("../../deep/struct.Foo.html"));
+        let page =
+            r#"<div><div id="a"><div>x</div><a href="b.html">b</a></div><div id="c"></div></div>"#;
+        assert_eq!(
+            element_by_id(page, "a"),
+            Some(r#"<div id="a"><div>x</div><a href="b.html">b</a></div>"#)
+        );
+        assert_eq!(element_by_id(page, "c"), Some(r#"<div id="c"></div>"#));
+        assert_eq!(hrefs(page), vec!["b.html"]);
+        let book = r#"<main><h1 id="a">A</h1><p>x</p><h2 id="b">B</h2><p>y</p><h2 id="c">C</h2></main><nav></nav>"#;
+        assert_eq!(section(book, "b"), Some(r#"<h2 id="b">B</h2><p>y</p>"#));
+        assert_eq!(
+            section(book, "a"),
+            Some(r#"<h1 id="a">A</h1><p>x</p><h2 id="b">B</h2><p>y</p><h2 id="c">C</h2>"#)
+        );
+        assert_eq!(section(page, "a"), None);
+        assert_eq!(
+            tokens(r#"<!-- x --><a class="kw" href="b.html">b &amp; c</a><br/>"#)
+                .collect::<Vec<_>>(),
+            vec![
+                Token::Start {
+                    name: "a",
+                    attrs: r#" class="kw" href="b.html""#,
+                    pos: 10
+                },
+                Token::Text("b &amp; c"),
+                Token::End("a"),
+                Token::Start {
+                    name: "br",
+                    attrs: "",
+                    pos: 51
+                }
+            ]
+        );
+        assert_eq!(attr(r#" data-class="x" class="kw""#, "class"), Some("kw"));
+        assert_eq!(unescape("&lt;&#39;_&#x3E; &amp x&amp;"), "<'_> &amp x&");
+    }
+}
@@ -0,0 +1,213 @@
+//! Implementors of traits across documented crates from `implementors/*.js`
+use crate::{
+    doc::ItemType,
+    html,
+    location::{self, LocationError, Resolver},
+    which, Error
+};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// An impl of a trait by a documented crate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Implementor {
+    pub krate: String,
+    /// The header such as `impl Shape for Widget`
+    pub header: String,
+    /// Auto traits and blanket impls
+    pub synthetic: bool,
+    /// The path and the kind of the implementing type
+    pub ty: Option<(String, ItemType)>,
+    /// The page of the implementing type
+    pub page: Option<PathBuf>
+}
+
+impl Implementor {
+    /// A line of list of the implementing type
+    pub fn line(&self) -> Option<String> {
+        self.ty
+            .as_ref()
+            .map(|(path, ty)| format!("{}\t{}", path, ty.as_str()))
+    }
+}
+
+/// `implementors/<crate>/<path>/trait.X.js`, or `trait.impl/` since rustdoc 1.76
+pub fn implementors_file(doc_dir: &Path, trait_page: &Path) -> Option<PathBuf> {
+    ["implementors", "trait.impl"]
+        .iter()
+        .map(|dir| doc_dir.join(dir).join(trait_page).with_extension("js"))
+        .find(|f| f.is_file())
+}
+
+/// Implementors of the trait in the std and local doc dirs
+///
+/// Crates of the local docs implement traits of std in the local doc dir.
+pub fn of_trait(resolver: &mut Resolver, line: &str) -> Result<Vec<Implementor>, Error> {
+    let item = resolver.item(line)?;
+    if item.ty != ItemType::Trait {
+        return Err(LocationError::NotTrait(item.full_path()).into());
+    }
+    let file = resolver.location(&item.line())?.file;
+    let doc_dir = which::doc_dir_of(&file)?;
+    let page = file
+        .strip_prefix(&doc_dir)
+        .map_err(|_| LocationError::FileNotFound)?;
+    let mut found = Vec::new();
+    for dir in resolver.doc_dirs()? {
+        found.extend(implementors(&dir, page)?);
+    }
+    Ok(found)
+}
+
+/// Implementors in the doc dir of the trait whose page is `trait_page` relative to a doc dir
+pub fn implementors(doc_dir: &Path, trait_page: &Path) -> Result<Vec<Implementor>, Error> {
+    let file = match implementors_file(doc_dir, trait_page) {
+        Some(file) => file,
+        None => return Ok(Vec::new())
+    };
+    let js = std::fs::read_to_string(&file)?;
+    let krates = parse_js(&js).map_err(|e| Error::SerdeJson(file.display().to_string(), e))?;
+    Ok(krates
+        .into_iter()
+        .flat_map(|(krate, entries)| {
+            entries
+                .into_iter()
+                .filter_map(move |e| implementor(doc_dir, &krate, &e))
+        })
+        .collect())
+}
+
+/// Entries of each crate of a js file that rustdoc registers per trait or type
+///
+/// Rustdoc 1.60 assigns `implementors["crate"] = [..];` per crate, and later versions pass
+/// `Object.fromEntries([["crate", [..]], ..])`.
+pub(crate) fn parse_js(js: &str) -> Result<Vec<(String, Vec<Value>)>, serde_json::Error> {
+    if let Some(i) = js.find("Object.fromEntries(") {
+        let rest = &js[i + "Object.fromEntries(".len()..];
+        let pairs: Vec<(String, Vec<Value>)> = first_value(rest)?;
+        return Ok(pairs);
+    }
+    let mut krates = Vec::new();
+    for line in js.lines() {
+        let name = line
+            .split_once(" = ")
+            .and_then(|(lhs, value)| Some((lhs.strip_suffix("\"]")?.rsplit_once("[\"")?.1, value)));
+        if let Some((name, value)) = name {
+            krates.push((name.to_owned(), first_value(value)?));
+        }
+    }
+    Ok(krates)
+}
+
+/// Parses the value at the start, ignoring the rest of the script
+fn first_value<T: serde::de::DeserializeOwned>(s: &str) -> Result<T, serde_json::Error> {
+    let mut values = serde_json::Deserializer::from_str(s).into_iter::<T>();
+    match values.next() {
+        Some(value) => value,
+        None => serde_json::from_str("")
+    }
+}
+
+/// `{"text":..,"synthetic":..,"types":[..]}` of 1.60, or `[text, negative]` followed by
+/// `1, types` for synthetic impls
+fn implementor(doc_dir: &Path, krate: &str, entry: &Value) -> Option<Implementor> {
+    let (text, synthetic) = match entry {
+        Value::Object(o) => (
+            o.get("text")?,
+            o.get("synthetic").and_then(Value::as_bool).unwrap_or(false)
+        ),
+        Value::Array(a) => (a.first()?, a.get(2).is_some()),
+        _ => return None
+    };
+    let text = text.as_str()?;
+    let (ty, page) = implementing_type(text)
+        .map(|(ty, href)| {
+            // Types of crates without local docs link to the web
+            let page = href
+                .filter(|h| !h.contains("://"))
+                .map(|h| location::normalize(&doc_dir.join(h)));
+            (Some(ty), page)
+        })
+        .unwrap_or_default();
+    Some(Implementor {
+        krate: krate.into(),
+        header: html::text(text),
+        synthetic,
+        ty,
+        page
+    })
+}
+
+/// The first linked type after ` for `, with `title="struct deep::Widget"`
+fn implementing_type(header: &str) -> Option<((String, ItemType), Option<String>)> {
+    let after_for = header.find(" for ")?;
+    html::tokens(&header[after_for..]).find_map(|token| match token {
+        html::Token::Start {
+            name: "a", attrs, ..
+        } => {
+            let (kind, path) = html::attr(attrs, "title")?.split_once(' ')?;
+            let ty = kind.parse().ok()?;
+            let href = html::attr(attrs, "href").map(html::unescape);
+            Some(((html::unescape(path), ty), href))
+        }
+        _ => None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn implementors_of_traits() {
+        let tree = crate::fixture::doc_tree();
+        let search_index = tree.search_index();
+        let doc_dir = tree.doc_dir();
+        let found = implementors(doc_dir, Path::new("deep/outer/inner/trait.Shape.html")).unwrap();
+        assert_eq!(
+            found,
+            vec![
+                Implementor {
+                    krate: "deep".into(),
+                    header: "impl Shape for Widget".into(),
+                    synthetic: false,
+                    ty: Some(("deep::outer::inner::Widget".into(), ItemType::Struct)),
+                    page: Some(doc_dir.join("deep/outer/inner/struct.Widget.html"))
+                },
+                Implementor {
+                    krate: "other".into(),
+                    header: "impl<T: Shape> Shape for Box<T>".into(),
+                    synthetic: false,
+                    ty: Some(("alloc::boxed::Box".into(), ItemType::Struct)),
+                    page: None
+                }
+            ]
+        );
+        assert_eq!(
+            found[0].line().as_deref(),
+            Some("deep::outer::inner::Widget\tstruct")
+        );
+        let js = r#"(function() {
+    const implementors = Object.fromEntries([["deep",[["impl Eq for <a class=\"enum\" href=\"deep/enum.Choice.html\" title=\"enum deep::Choice\">Choice</a>",0],["impl Send for Choice",0,1,["deep::Choice"]]]]]);
+    if (window.register_implementors) { window.register_implementors(implementors); }
+})()"#;
+        let krates = parse_js(js).unwrap();
+        assert_eq!(krates.len(), 1);
+        assert!(
+            implementor(doc_dir, "deep", &krates[0].1[1])
+                .unwrap()
+                .synthetic
+        );
+        let choice = implementor(doc_dir, &krates[0].0, &krates[0].1[0]).unwrap();
+        assert_eq!(choice.ty, Some(("deep::Choice".into(), ItemType::Enum)));
+        let mut resolver = Resolver::for_test(search_index.clone());
+        assert_eq!(
+            of_trait(&mut resolver, "deep::outer::inner::Shape").unwrap(),
+            found
+        );
+        assert!(of_trait(&mut resolver, "deep::outer::inner::Widget").is_err());
+        assert!(implementors(doc_dir, Path::new("deep/trait.None.html"))
+            .unwrap()
+            .is_empty());
+    }
+}
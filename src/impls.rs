@@ -0,0 +1,346 @@
+//! Impl blocks of a type from its page, `trait.impl` and `type.impl` data
+use crate::{
+    html::{self, Token},
+    implementors,
+    location::{LocationError, Resolver},
+    signature, which, Error
+};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImplKind {
+    Inherent,
+    Trait,
+    /// Auto traits such as `Send`
+    Synthetic,
+    Blanket
+}
+
+impl ImplKind {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ImplKind::Inherent => "inherent",
+            ImplKind::Trait => "trait",
+            ImplKind::Synthetic => "synthetic",
+            ImplKind::Blanket => "blanket"
+        }
+    }
+}
+
+/// An impl block and the page with its `impl-Trait-for-Type` anchor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Impl {
+    pub kind: ImplKind,
+    /// The header such as `impl Display for Widget`
+    pub header: String,
+    pub page: PathBuf,
+    pub anchor: Option<String>
+}
+
+/// Impls of the type of the line in the std and local docs
+pub fn of_type(resolver: &mut Resolver, line: &str) -> Result<Vec<Impl>, Error> {
+    let (definition, page) = resolver.definition_page(line)?;
+    impls(&resolver.doc_dirs()?, &page, &definition)
+}
+
+/// Impls on the page of the type, then impls recorded by other crates
+///
+/// `path` is where the type is defined, as the titles of links name it.
+pub fn impls(doc_dirs: &[PathBuf], page: &Path, path: &str) -> Result<Vec<Impl>, Error> {
+    let contents = std::fs::read_to_string(page)?;
+    let mut found = impl_blocks(&contents)
+        .into_iter()
+        .map(|(kind, header, id)| Impl {
+            kind,
+            header,
+            page: page.to_owned(),
+            anchor: Some(id)
+        })
+        .collect::<Vec<_>>();
+    let mut push = |imp: Impl| {
+        if !found.iter().any(|f| f.header == imp.header) {
+            found.push(imp);
+        }
+    };
+    let on_page = traits_on_page(&contents);
+    for doc_dir in doc_dirs {
+        for imp in cross_crate(doc_dirs, doc_dir, path, &on_page)? {
+            push(imp);
+        }
+    }
+    let doc_dir = which::doc_dir_of(page)?;
+    let rel = page
+        .strip_prefix(&doc_dir)
+        .map_err(|_| LocationError::FileNotFound)?;
+    for doc_dir in doc_dirs {
+        let file = doc_dir.join("type.impl").join(rel).with_extension("js");
+        if !file.is_file() {
+            continue;
+        }
+        let js = std::fs::read_to_string(&file)?;
+        let krates = implementors::parse_js(&js)
+            .map_err(|e| Error::SerdeJson(file.display().to_string(), e))?;
+        let blocks = krates
+            .iter()
+            .flat_map(|(_, entries)| entries)
+            .filter_map(|e| e.as_array()?.first()?.as_str())
+            .flat_map(impl_blocks);
+        for (kind, header, id) in blocks {
+            // The ids are of the pages of type aliases
+            let anchor = html::element_by_id(&contents, &id).map(|_| id);
+            push(Impl {
+                kind,
+                header,
+                page: page.to_owned(),
+                anchor
+            });
+        }
+    }
+    Ok(found)
+}
+
+/// Impls for the type in `implementors/` and `trait.impl/` of the doc dir, at the trait pages
+///
+/// Only the crate of the trait or of the type can implement it, and the page of the type has the
+/// impls of its own crate, so the files of traits of that crate and of `on_page` are not read.
+fn cross_crate(
+    doc_dirs: &[PathBuf],
+    doc_dir: &Path,
+    path: &str,
+    on_page: &[String]
+) -> Result<Vec<Impl>, Error> {
+    let krate = path.split("::").next().unwrap_or_default();
+    // As in `title="struct deep::Widget"`, not matching `deep::WidgetExt`
+    let title = format!(" {}\\\"", path);
+    let mut found = Vec::new();
+    for dir in ["implementors", "trait.impl"] {
+        let dir = doc_dir.join(dir);
+        for file in js_files(&dir)? {
+            let rel = file.strip_prefix(&dir).unwrap_or(&file);
+            let trait_path = match trait_path(rel) {
+                Some(p) => p,
+                None => continue
+            };
+            if trait_path.split("::").next() == Some(krate) || on_page.contains(&trait_path) {
+                continue;
+            }
+            let js = std::fs::read_to_string(&file)?;
+            if !js.contains(&title) {
+                continue;
+            }
+            let rel = rel.with_extension("html");
+            let trait_page = match doc_dirs.iter().map(|d| d.join(&rel)).find(|p| p.is_file()) {
+                Some(p) => p,
+                None => continue
+            };
+            let on_trait_page = std::fs::read_to_string(&trait_page)
+                .map(|c| impl_blocks(&c))
+                .unwrap_or_default();
+            for imp in implementors::implementors(doc_dir, &rel)? {
+                if imp.ty.as_ref().map(|(p, _)| p.as_str()) != Some(path) {
+                    continue;
+                }
+                let header = signature::one_line(&imp.header);
+                let anchor = on_trait_page
+                    .iter()
+                    .find(|(_, h, _)| *h == header)
+                    .map(|(_, _, id)| id.clone());
+                let kind = if imp.synthetic {
+                    ImplKind::Synthetic
+                } else {
+                    ImplKind::Trait
+                };
+                found.push(Impl {
+                    kind,
+                    header,
+                    page: trait_page.clone(),
+                    anchor
+                });
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// `deep::Shape` of `deep/trait.Shape.js`
+fn trait_path(rel: &Path) -> Option<String> {
+    let mut segments = rel.iter().map(|s| s.to_str()).collect::<Option<Vec<_>>>()?;
+    let name = segments
+        .pop()?
+        .strip_prefix("trait.")?
+        .strip_suffix(".js")?;
+    segments.push(name);
+    Some(segments.join("::"))
+}
+
+/// Traits in the headers of impls on the page, from `title="trait core::fmt::Display"`
+fn traits_on_page(contents: &str) -> Vec<String> {
+    contents
+        .match_indices("code-header")
+        .filter_map(|(i, _)| html::element_at(contents, contents[..i].rfind('<')?))
+        .filter(|header| html::text(header).starts_with("impl"))
+        .flat_map(|header| {
+            html::tokens(header).filter_map(|token| match token {
+                Token::Start {
+                    name: "a", attrs, ..
+                } => Some(html::unescape(
+                    html::attr(attrs, "title")?.strip_prefix("trait ")?
+                )),
+                _ => None
+            })
+        })
+        .collect()
+}
+
+fn js_files(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+    for entry in dir.read_dir()? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(js_files(&path)?);
+        } else if path.extension().is_some_and(|e| e == "js") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Elements with `impl` ids and classes, with the kind from the list they are in
+pub fn impl_blocks(contents: &str) -> Vec<(ImplKind, String, String)> {
+    let list = |id: &str| {
+        let start = contents.find(&format!(r#"id="{}""#, id))?;
+        let start = contents[..start].rfind('<')?;
+        Some(start..start + html::element_at(contents, start)?.len())
+    };
+    let synthetic = list("synthetic-implementations-list");
+    let blanket = list("blanket-implementations-list");
+    html::tokens(contents)
+        .filter_map(|token| {
+            let (attrs, pos) = match token {
+                Token::Start { attrs, pos, .. } => (attrs, pos),
+                _ => return None
+            };
+            let id =
+                html::attr(attrs, "id").filter(|id| *id == "impl" || id.starts_with("impl-"))?;
+            let class = html::attr(attrs, "class").unwrap_or_default();
+            if !class.split(' ').any(|c| c == "impl") {
+                return None;
+            }
+            let element = html::element_at(contents, pos)?;
+            let header = element
+                .find("code-header")
+                .and_then(|i| element[..i].rfind('<'))
+                .and_then(|i| html::element_at(element, i))?;
+            let header = signature::one_line(&html::text(header));
+            let kind = if synthetic.as_ref().is_some_and(|r| r.contains(&pos)) {
+                ImplKind::Synthetic
+            } else if blanket.as_ref().is_some_and(|r| r.contains(&pos)) {
+                ImplKind::Blanket
+            } else if header.contains(" for ") {
+                ImplKind::Trait
+            } else {
+                ImplKind::Inherent
+            };
+            Some((kind, header, id.to_owned()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::DocTree;
+
+    /// Impls in other crates of rustdoc 1.77 and later
+    const TYPE_IMPL: (&str, &str) = (
+        "type.impl/deep/outer/inner/struct.Widget.js",
+        r#"(function() {
+    var type_impls = Object.fromEntries([["other",[["<details class=\"toggle implementors-toggle\" open><summary><section id=\"impl-Paint-for-Widget\" class=\"impl\"><h3 class=\"code-header\">impl Paint for Widget</h3></section></summary></details>",0,["other::Gadget"]]]]]);
+    if (window.register_type_impls) { window.register_type_impls(type_impls); }
+})()"#
+    );
+
+    /// A trait of another crate implemented there for `Widget`, and for a type of similar name
+    const PAINT: [(&str, &str); 2] = [
+        (
+            "implementors/other/trait.Paint.js",
+            r#"(function() {var implementors = {};
+implementors["other"] = [{"text":"impl <a class=\"trait\" href=\"other/trait.Paint.html\" title=\"trait other::Paint\">Paint</a> for <a class=\"struct\" href=\"deep/outer/inner/struct.Widget.html\" title=\"struct deep::outer::inner::Widget\">Widget</a>","synthetic":false,"types":["deep::outer::inner::Widget"]},{"text":"impl <a class=\"trait\" href=\"other/trait.Paint.html\" title=\"trait other::Paint\">Paint</a> for <a class=\"struct\" href=\"deep/outer/inner/struct.WidgetExt.html\" title=\"struct deep::outer::inner::WidgetExt\">WidgetExt</a>","synthetic":false,"types":["deep::outer::inner::WidgetExt"]}];
+if (window.register_implementors) {window.register_implementors(implementors);} else {window.pending_implementors = implementors;}})()"#
+        ),
+        (
+            "other/trait.Paint.html",
+            r#"<h1>Trait Paint</h1>
+<h2 id="implementors" class="small-section-header">Implementors</h2>
+<div id="implementors-list"><div id="impl-Paint-for-Widget" class="impl"><h3 class="code-header">impl Paint for Widget</h3></div><div id="impl-Paint-for-WidgetExt" class="impl"><h3 class="code-header">impl Paint for WidgetExt</h3></div></div>"#
+        )
+    ];
+
+    #[test]
+    fn impls_of_widget() {
+        let tree = DocTree::new(&[TYPE_IMPL, PAINT[0], PAINT[1]]);
+        let search_index = tree.search_index();
+        let doc_dir = tree.doc_dir();
+        let mut resolver = Resolver::for_test(search_index.clone());
+        let widget = doc_dir.join("deep/outer/inner/struct.Widget.html");
+        let found = of_type(&mut resolver, "deep::outer::inner::Widget")
+            .unwrap()
+            .into_iter()
+            .map(|i| (i.kind, i.header, i.page, i.anchor))
+            .collect::<Vec<_>>();
+        let on_page = |kind, header: &str, id: Option<&str>| {
+            (
+                kind,
+                header.to_owned(),
+                widget.clone(),
+                id.map(str::to_owned)
+            )
+        };
+        assert_eq!(
+            found,
+            vec![
+                on_page(ImplKind::Inherent, "impl Widget", Some("impl")),
+                on_page(
+                    ImplKind::Trait,
+                    "impl Display for Widget",
+                    Some("impl-Display-for-Widget")
+                ),
+                on_page(
+                    ImplKind::Trait,
+                    "impl Debug for Widget",
+                    Some("impl-Debug-for-Widget")
+                ),
+                on_page(
+                    ImplKind::Synthetic,
+                    "impl Send for Widget",
+                    Some("impl-Send-for-Widget")
+                ),
+                on_page(
+                    ImplKind::Blanket,
+                    "impl<T> From<T> for T",
+                    Some("impl-From%3CT%3E-for-T")
+                ),
+                // The impl in the type.impl data is the same
+                (
+                    ImplKind::Trait,
+                    "impl Paint for Widget".into(),
+                    doc_dir.join("other/trait.Paint.html"),
+                    Some("impl-Paint-for-Widget".into())
+                )
+            ]
+        );
+        assert_eq!(
+            traits_on_page(
+                r#"<section id="impl-Display-for-Widget" class="impl"><h3 class="code-header">impl <a class="trait" href="https://doc.rust-lang.org/1.60.0/core/fmt/trait.Display.html" title="trait core::fmt::Display">Display</a> for Widget</h3></section>"#
+            ),
+            vec!["core::fmt::Display".to_owned()]
+        );
+        assert_eq!(
+            trait_path(Path::new("core/fmt/trait.Display.js")).as_deref(),
+            Some("core::fmt::Display")
+        );
+    }
+}
@@ -3,9 +3,26 @@ extern crate serde;
 #[macro_use]
 extern crate thiserror;
 
+pub mod book;
 pub mod doc;
+pub mod errors;
+pub mod examples;
+pub mod files;
+#[cfg(test)]
+mod fixture;
+pub mod html;
+pub mod implementors;
+pub mod impls;
 pub mod location;
+pub mod markdown;
+pub mod open;
+pub mod preview;
 pub mod search_index;
+pub mod signature;
+pub mod source;
+pub mod view;
+pub mod which;
+pub mod workspace;
 
 use rayon::prelude::*;
 use std::{
@@ -24,14 +41,30 @@ pub enum Error {
     SerdeJson(String, serde_json::error::Error),
     #[error(transparent)]
     Metadata(#[from] cargo_metadata::Error),
+    #[error("Package {0} not found")]
+    PackageNotFound(String),
     #[error(transparent)]
     Join(#[from] tokio::task::JoinError),
     #[error(transparent)]
-    Location(#[from] location::LocationError)
+    Location(#[from] location::LocationError),
+    #[error(transparent)]
+    Url(#[from] url::ParseError),
+    #[error("No command is found to open urls. Please set $BROWSER or --browser")]
+    OpenerNotFound,
+    #[error("{0} failed with {1}")]
+    OpenerFailed(String, std::process::ExitStatus)
 }
 
 pub fn read_search_index<P: AsRef<Path>>(
     src: P
+) -> Result<impl rayon::iter::ParallelIterator<Item = Result<(String, doc::Crate), Error>>, Error> {
+    read_search_index_of(src, |_| true)
+}
+
+/// Parses only the lines of crates that `keep` accepts
+pub fn read_search_index_of<P: AsRef<Path>>(
+    src: P,
+    keep: impl Fn(&str) -> bool + Send + Sync
 ) -> Result<impl rayon::iter::ParallelIterator<Item = Result<(String, doc::Crate), Error>>, Error> {
     let file = File::open(src.as_ref())?;
     let reader = BufReader::new(file);
@@ -53,9 +86,33 @@ pub fn read_search_index<P: AsRef<Path>>(
                 true
             }
         })
+        .filter(move |l| match l {
+            Ok(l) => crate_of_line(l).is_none_or(&keep),
+            Err(_) => true
+        })
         .map(|l: Result<String, Error>| l.and_then(parse_line)))
 }
 
+/// The name of the crate of a line `"name":{..},`
+pub fn crate_of_line(line: &str) -> Option<&str> {
+    let (name, _) = line.strip_prefix('"')?.split_once("\":")?;
+    Some(name)
+}
+
+/// Parses only the line of `krate_name`
+pub fn read_crate<P: AsRef<Path>>(src: P, krate_name: &str) -> Result<Option<doc::Crate>, Error> {
+    let file = File::open(src.as_ref())?;
+    let reader = BufReader::new(file);
+    let prefix = format!("\"{}\":", krate_name);
+    for line in reader.lines().skip(1) {
+        let line = line?;
+        if line.starts_with(&prefix) {
+            return parse_line(line).map(|(_, krate)| Some(krate));
+        }
+    }
+    Ok(None)
+}
+
 /// Parses one line `"name":{..},`
 pub fn parse_line(line: String) -> Result<(String, doc::Crate), Error> {
     let mut line = {
@@ -87,3 +144,12 @@ pub fn parse_line(line: String) -> Result<(String, doc::Crate), Error> {
         Ok(krate) => Ok((name, krate))
     }
 }
+
+/// The single-quoted string passed to a call such as `JSON.parse('..')`
+pub(crate) fn js_string(js: &str) -> Option<String> {
+    let start = js.find("('")? + 2;
+    let end = js.rfind("')")?;
+    // Lines of long strings end with backslashes
+    let quoted = js.get(start..end)?.replace("\\\n", "");
+    unescape::unescape(&quoted)
+}
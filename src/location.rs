@@ -1,11 +1,16 @@
 use crate::{
-    doc::{ItemType, ParseItemTypeError, FILETYPE, STD_PRIMITIVES},
+    doc::{Crate, Item, ItemType, ParentType, ParseItemTypeError, FILETYPE, STD_PRIMITIVES},
+    search_index::{Manifest, RootKind},
+    workspace::{SourceKind, Workspace},
     Error
 };
+use rayon::prelude::*;
 use std::{
-    path::{Path, PathBuf},
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
     str::FromStr
 };
+use url::Url;
 
 #[derive(Debug, Error)]
 pub enum LocationError {
@@ -18,30 +23,637 @@ pub enum LocationError {
     #[error("Item not found")]
     ItemNotFound,
     #[error("Doc dir not found")]
-    DocNotFound
+    DocNotFound,
+    #[error("Declaration not found")]
+    DeclarationNotFound,
+    #[error("Source not found")]
+    SourceNotFound,
+    #[error("{0} is not a trait")]
+    NotTrait(String),
+    #[error("Ambiguous path:\n{}", .0.join("\n"))]
+    Ambiguous(Vec<String>)
 }
 
 pub const STD_CRATES: &[&str] = &["alloc", "core", "proc_macro", "std", "test"];
 
-pub async fn location_from_line(line: &str, current_dir: Option<PathBuf>) -> Result<String, Error> {
-    let (path_components, ty) = parse_line(line)?;
-    let (krate_name, tail): (_, &[&str]) = split_krate(&path_components)?;
-    let search_index: PathBuf = find_search_index(krate_name, current_dir)?;
-    find(&search_index, krate_name, tail, ty)
+/// An installed page of docs and its url
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub file: PathBuf,
+    pub anchor: Option<String>,
+    /// A `file://` url, or a web url in [`UrlStyle::Web`] or [`UrlStyle::Base`]
+    pub url: Url
 }
 
+impl Location {
+    /// Percent-encodes the path into a `file://` url
+    pub fn new(file: PathBuf, anchor: Option<String>) -> Result<Self, Error> {
+        let mut url = Url::from_file_path(&file).map_err(|_| LocationError::FileNotFound)?;
+        url.set_fragment(anchor.as_deref());
+        Ok(Self { file, anchor, url })
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { self.url.fmt(f) }
+}
+
+pub async fn location_from_line(line: &str, manifest: &Manifest) -> Result<Location, Error> {
+    Resolver::new(manifest.clone()).location(line)
+}
+
+/// Resolves lines in input order with one [`Resolver`]
+pub fn locations_from_lines<'a, I>(
+    lines: I,
+    manifest: &Manifest
+) -> impl Iterator<Item = Result<Location, Error>> + 'a
+where
+    I: IntoIterator + 'a,
+    I::Item: AsRef<str>
+{
+    let mut resolver = Resolver::new(manifest.clone());
+    lines
+        .into_iter()
+        .map(move |l| resolver.location(l.as_ref()))
+}
+
+struct Resolved {
+    public: String,
+    doc_dir: PathBuf,
+    file: PathBuf,
+    id: Option<String>
+}
+
+/// Where urls point to
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum UrlStyle {
+    /// Installed docs
+    #[default]
+    File,
+    /// doc.rust-lang.org for std and docs.rs for crates from registries
+    Web,
+    /// A docs host which serves the doc dir at the url
+    Base(String)
+}
+
+/// Keeps discovered doc dirs and parsed crates between lines
+#[derive(Debug, Default)]
+pub struct Resolver {
+    manifest: Manifest,
+    url_style: UrlStyle,
+    std: Option<Option<PathBuf>>,
+    local: Option<Option<PathBuf>>,
+    krates: HashMap<String, Option<Vec<Item>>>,
+    loaded_all: bool,
+    channel: Option<Option<String>>,
+    workspace: Option<Workspace>,
+    books: HashMap<String, Vec<crate::book::Section>>
+}
+
+impl Resolver {
+    pub fn new(manifest: Manifest) -> Self {
+        Self {
+            manifest,
+            ..Default::default()
+        }
+    }
+
+    /// Resolves only the local docs of the search index
+    #[cfg(test)]
+    pub(crate) fn for_test(search_index: PathBuf) -> Self {
+        Self {
+            std: Some(None),
+            local: Some(Some(search_index)),
+            ..Default::default()
+        }
+    }
+
+    pub fn url_style(mut self, url_style: UrlStyle) -> Self {
+        self.url_style = url_style;
+        self
+    }
+
+    /// Accepts a line of list or a bare path such as `HashMap::insert`
+    pub fn location(&mut self, line: &str) -> Result<Location, Error> {
+        if crate::book::is_section(line) {
+            return self.section(line);
+        }
+        if crate::errors::is_error(line) {
+            let doc_dir = self.std_doc_dir_with(crate::errors::has_index)?;
+            let (file, id) = crate::errors::find(&doc_dir, line)?;
+            return self.locate(&doc_dir, file, id);
+        }
+        let Resolved {
+            doc_dir, file, id, ..
+        } = self.resolve(line)?;
+        self.locate(&doc_dir, file, id)
+    }
+
+    /// Reads each book once for lines of `--stdin`
+    fn section(&mut self, line: &str) -> Result<Location, Error> {
+        let path = line.split_whitespace().next().unwrap_or_default();
+        let name = path.split("::").next().unwrap_or_default();
+        let doc_dir = self.book_doc_dir(name)?;
+        if !self.books.contains_key(name) {
+            let index =
+                crate::book::index_file(&doc_dir.join(name)).ok_or(LocationError::DocNotFound)?;
+            let sections = crate::book::read_book(name, &index)?;
+            self.books.insert(name.into(), sections);
+        }
+        let (file, id) = self.books[name]
+            .iter()
+            .find(|s| s.path == path)
+            .map(|s| (doc_dir.join(&s.file), s.anchor.clone()))
+            .ok_or(LocationError::ItemNotFound)?;
+        self.locate(&doc_dir, file, id)
+    }
+
+    /// The doc dir that has the book, usually the one of std
+    fn book_doc_dir(&mut self, name: &str) -> Result<PathBuf, Error> {
+        self.std_doc_dir_with(|dir| crate::book::index_file(&dir.join(name)).is_some())
+    }
+
+    /// The first doc dir that has the pages, usually the one of std
+    fn std_doc_dir_with(&mut self, has: impl Fn(&Path) -> bool) -> Result<PathBuf, Error> {
+        if let Some(dir) = self.doc_dirs()?.into_iter().find(|d| has(d)) {
+            return Ok(dir);
+        }
+        // Newer std docs have no search-index.js but still have books
+        let std = crate::search_index::find_std_doc_dir()?;
+        Ok(std.filter(|d| has(d)).ok_or(LocationError::DocNotFound)?)
+    }
+
+    /// Makes the url of the installed page in [`UrlStyle`]
+    fn locate(
+        &mut self,
+        doc_dir: &Path,
+        file: PathBuf,
+        id: Option<String>
+    ) -> Result<Location, Error> {
+        let rel = match file.strip_prefix(doc_dir) {
+            Ok(rel) => rel
+                .iter()
+                .map(|c| c.to_string_lossy().into_owned())
+                .collect::<Vec<_>>(),
+            Err(_) => return Location::new(file, id)
+        };
+        let krate_name = rel.first().map_or("", String::as_str);
+        let base = match &self.url_style {
+            UrlStyle::File => None,
+            UrlStyle::Base(base) => Some(base.clone()),
+            UrlStyle::Web
+                if is_std_krate(krate_name)
+                    || krate_name == crate::errors::OLD_INDEX
+                    || crate::book::index_file(&doc_dir.join(krate_name)).is_some() =>
+            {
+                let channel = match &self.channel {
+                    Some(x) => x.clone(),
+                    None => self
+                        .channel
+                        .insert(crate::search_index::rustc_channel()?)
+                        .clone()
+                };
+                channel.map(|c| format!("https://doc.rust-lang.org/{}", c))
+            }
+            UrlStyle::Web => {
+                if self.workspace.is_none() {
+                    self.workspace = Some(Workspace::load(&self.manifest)?);
+                }
+                let workspace = self.workspace.as_ref().unwrap();
+                // Only crates from registries are on docs.rs
+                workspace
+                    .info(krate_name)
+                    .filter(|i| i.source == SourceKind::Registry)
+                    .map(|i| format!("https://docs.rs/{}/{}", i.name, i.version))
+            }
+        };
+        let base = match base {
+            Some(base) => base,
+            None => return Location::new(file, id)
+        };
+        let mut url = Url::parse(&base)?;
+        url.path_segments_mut()
+            .map_err(|_| url::ParseError::RelativeUrlWithCannotBeABaseBase)?
+            .pop_if_empty()
+            .extend(&rel);
+        url.set_fragment(id.as_deref());
+        Ok(Location {
+            file,
+            anchor: id,
+            url
+        })
+    }
+
+    /// The public path and the path where the item is defined, after following re-exports
+    pub fn definition(&mut self, line: &str) -> Result<(String, String), Error> {
+        let (resolved, definition) = self.resolve_definition(line)?;
+        Ok((resolved.public, definition))
+    }
+
+    /// The path where the item is defined and the page of the item, resolving the line once
+    pub fn definition_page(&mut self, line: &str) -> Result<(String, PathBuf), Error> {
+        let (resolved, definition) = self.resolve_definition(line)?;
+        Ok((definition, resolved.file))
+    }
+
+    fn resolve_definition(&mut self, line: &str) -> Result<(Resolved, String), Error> {
+        let resolved = self.resolve(line)?;
+        let definition = item_from_page(&resolved.doc_dir, &resolved.file, resolved.id.as_deref())
+            .map_or_else(|| resolved.public.clone(), |(path, _)| path);
+        Ok((resolved, definition))
+    }
+
+    /// The item in every impl block on the page, such as `method.fmt` and `method.fmt-1` of
+    /// `Display` and `Debug`, with the id of the impl block
+    pub fn impls(&mut self, line: &str) -> Result<Vec<(Option<String>, Location)>, Error> {
+        let Resolved {
+            doc_dir, file, id, ..
+        } = self.resolve(line)?;
+        let id = match id {
+            Some(id) => id,
+            None => return Ok(vec![(None, self.locate(&doc_dir, file, None)?)])
+        };
+        let contents = std::fs::read_to_string(&file)?;
+        let mut anchors = impl_anchors(&contents, &id);
+        if anchors.is_empty() {
+            anchors.push((None, id));
+        }
+        anchors
+            .into_iter()
+            .map(|(imp, anchor)| Ok((imp, self.locate(&doc_dir, file.clone(), Some(anchor))?)))
+            .collect()
+    }
+
+    /// Chooses the item in the impl of a trait such as `Debug` or `fmt::Debug`
+    pub fn location_in_impl(&mut self, line: &str, trait_name: &str) -> Result<Location, Error> {
+        let name = trait_name.rsplit("::").next().unwrap_or(trait_name);
+        self.impls(line)?
+            .into_iter()
+            .find(|(imp, _)| imp.as_deref().is_some_and(|imp| is_impl_of(imp, name)))
+            .map(|(_, location)| location)
+            .ok_or_else(|| LocationError::ItemNotFound.into())
+    }
+
+    fn resolve(&mut self, line: &str) -> Result<Resolved, Error> {
+        let (path_components, ty) = parse_query(line)?;
+        let ty = match ty {
+            Some(ty) => ty,
+            None => {
+                let item = self.lookup(&path_components.join("::"))?;
+                return self.resolve(&item.line());
+            }
+        };
+        let (krate_name, tail): (_, &[&str]) = split_krate(&path_components)?;
+        let search_index: PathBuf = self.search_index(krate_name)?;
+        let items = self.items(&search_index, krate_name)?;
+        let (file, id) = resolve_with(&search_index, items, krate_name, tail, ty)?;
+        let (file, id) = follow(file, id);
+        Ok(Resolved {
+            public: path_components.join("::"),
+            doc_dir: search_index.parent().unwrap().into(),
+            file,
+            id
+        })
+    }
+
+    /// The item of the index for a line of list or a bare path
+    pub fn item(&mut self, line: &str) -> Result<Item, Error> {
+        let (path_components, ty) = parse_query(line)?;
+        let path = path_components.join("::");
+        let ty = match ty {
+            Some(ty) => ty,
+            None => return self.lookup(&path)
+        };
+        let search_index = self.search_index(path_components[0])?;
+        self.items(&search_index, path_components[0])?
+            .into_iter()
+            .flatten()
+            .find(|i| i.ty == ty && i.full_path() == path)
+            .cloned()
+            .ok_or_else(|| LocationError::ItemNotFound.into())
+    }
+
+    /// Picks the item of the highest kind priority or fails with the candidates
+    pub fn lookup(&mut self, path: &str) -> Result<Item, Error> {
+        let mut candidates = self.candidates(path)?;
+        let best = candidates.iter().map(|i| kind_priority(i.ty)).min();
+        candidates.retain(|i| Some(kind_priority(i.ty)) == best);
+        // std re-exports most of core and alloc
+        let in_std = |i: &Item| {
+            let full_path = i.full_path();
+            let tail = full_path.split_once("::").map_or("", |(_, t)| t);
+            candidates
+                .iter()
+                .any(|c| c.ty == i.ty && c.full_path() == format!("std::{}", tail))
+        };
+        let shadowed = candidates
+            .iter()
+            .filter(|i| matches!(i.path.split("::").next(), Some("core" | "alloc")) && in_std(i))
+            .map(Item::line)
+            .collect::<Vec<_>>();
+        candidates.retain(|i| !shadowed.contains(&i.line()));
+        match candidates.len() {
+            0 => Err(LocationError::ItemNotFound.into()),
+            1 => Ok(candidates.remove(0)),
+            _ => Err(LocationError::Ambiguous(candidates.iter().map(Item::line).collect()).into())
+        }
+    }
+
+    /// Items whose path is `path` or ends with `::path`, without duplicated lines
+    pub fn candidates(&mut self, path: &str) -> Result<Vec<Item>, Error> {
+        let krate_name = path.split("::").next().unwrap_or_default();
+        let krates: Vec<&[Item]> = match self.search_index(krate_name) {
+            Ok(search_index) if path.contains("::") => {
+                self.items(&search_index, krate_name)?.into_iter().collect()
+            }
+            _ => Vec::new()
+        };
+        let exact = krates
+            .iter()
+            .flat_map(|items| items.iter())
+            .filter(|i| i.full_path() == path)
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut found = if exact.is_empty() {
+            let suffix = format!("::{}", path);
+            self.load_all()?;
+            self.krates
+                .values()
+                .flatten()
+                .flat_map(|items| items.iter())
+                .filter(|i| i.full_path().ends_with(&suffix))
+                .cloned()
+                .collect()
+        } else {
+            exact
+        };
+        found.sort_by_key(|i| (kind_priority(i.ty), i.line()));
+        found.dedup_by_key(|i| i.line());
+        Ok(found)
+    }
+
+    fn search_index(&mut self, krate_name: &str) -> Result<PathBuf, Error> {
+        if is_std_krate(krate_name) {
+            self.root_search_index(RootKind::Std)
+        } else {
+            self.root_search_index(RootKind::Local)
+        }
+    }
+
+    /// The search index of the std or local docs, found once
+    fn root_search_index(&mut self, root: RootKind) -> Result<PathBuf, Error> {
+        let found = match root {
+            RootKind::Std => match &self.std {
+                Some(x) => x.clone(),
+                None => self.std.insert(crate::search_index::find_std()?).clone()
+            },
+            RootKind::Local => match &self.local {
+                Some(x) => x.clone(),
+                None => {
+                    let local = crate::search_index::find_local(&self.manifest)?;
+                    self.local.insert(local).clone()
+                }
+            }
+        };
+        Ok(found.ok_or(LocationError::DocNotFound)?)
+    }
+
+    /// The installed std and local doc dirs
+    pub fn doc_dirs(&mut self) -> Result<Vec<PathBuf>, Error> {
+        let mut dirs = Vec::new();
+        for root in [RootKind::Std, RootKind::Local] {
+            match self.root_search_index(root) {
+                Ok(search_index) => dirs.extend(search_index.parent().map(PathBuf::from)),
+                Err(Error::Location(LocationError::DocNotFound)) => {}
+                Err(e) => return Err(e)
+            }
+        }
+        dirs.dedup();
+        Ok(dirs)
+    }
+
+    /// Parses every crate of the std and local docs
+    fn load_all(&mut self) -> Result<(), Error> {
+        if self.loaded_all {
+            return Ok(());
+        }
+        for root in [RootKind::Std, RootKind::Local] {
+            let search_index = match self.root_search_index(root) {
+                Ok(x) => x,
+                Err(Error::Location(LocationError::DocNotFound)) => continue,
+                Err(e) => return Err(e)
+            };
+            let krates = crate::read_search_index(search_index)?
+                .map(|r| r.map(|(name, krate)| (name, krate.entries())))
+                .collect::<Result<Vec<_>, _>>()?;
+            for (name, items) in krates {
+                self.krates.entry(name).or_insert(Some(items));
+            }
+        }
+        self.loaded_all = true;
+        Ok(())
+    }
+
+    fn items(&mut self, search_index: &Path, krate_name: &str) -> Result<Option<&[Item]>, Error> {
+        if !self.krates.contains_key(krate_name) {
+            let items = crate::read_crate(search_index, krate_name)?.map(Crate::entries);
+            self.krates.insert(krate_name.into(), items);
+        }
+        Ok(self.krates[krate_name].as_deref())
+    }
+}
+
+/// Computes the url from the index and falls back to probing files
+fn resolve_with(
+    search_index: &Path,
+    items: Option<&[Item]>,
+    krate_name: &str,
+    tail: &[&str],
+    ty: ItemType
+) -> Result<(PathBuf, Option<String>), Error> {
+    let doc_dir: &Path = search_index.parent().unwrap();
+    if let Some(items) = items {
+        let full_path = std::iter::once(krate_name)
+            .chain(tail.iter().copied())
+            .collect::<Vec<_>>()
+            .join("::");
+        let item = items
+            .iter()
+            .find(|i| i.ty == ty && i.full_path() == full_path);
+        if let Some(item) = item {
+            let (file, id) = item_file(item);
+            let file = doc_dir.join(file);
+            if file.is_file() {
+                return Ok((file, id));
+            }
+        }
+    }
+    find(search_index, krate_name, tail, ty)
+}
+
+/// Follows `reexport` anchors and redirect stubs to the page that defines the item
+pub(crate) fn follow(file: PathBuf, id: Option<String>) -> (PathBuf, Option<String>) {
+    let mut cur = (file, id);
+    // Guards against redirect loops
+    for _ in 0..8 {
+        match next_page(&cur.0, cur.1.as_deref()) {
+            Some(next) => cur = next,
+            None => break
+        }
+    }
+    cur
+}
+
+fn next_page(file: &Path, id: Option<&str>) -> Option<(PathBuf, Option<String>)> {
+    let is_reexport = id.is_some_and(|id| id.starts_with("reexport."));
+    // Redirect stubs are tiny, so large pages are not read for them
+    if !is_reexport && file.metadata().ok()?.len() > 4096 {
+        return None;
+    }
+    let contents = std::fs::read_to_string(file).ok()?;
+    let href = match id {
+        Some(id) if is_reexport => {
+            *crate::html::hrefs(crate::html::element_by_id(&contents, id)?).last()?
+        }
+        _ => crate::html::redirect(&contents)?
+    };
+    let (target, anchor) = match href.split_once('#') {
+        Some((target, anchor)) => (target, Some(anchor.to_owned())),
+        None if is_reexport => (href, None),
+        None => (href, id.map(str::to_owned))
+    };
+    let target = normalize(&file.parent()?.join(target));
+    target.is_file().then_some((target, anchor))
+}
+
+/// Rustdoc suffixes a duplicated id with `-1`, `-2`, ... in the order of impl blocks
+fn impl_anchors(contents: &str, id: &str) -> Vec<(Option<String>, String)> {
+    let attr = format!(r#"id="{}"#, id);
+    contents
+        .match_indices(&attr)
+        .filter_map(|(pos, _)| {
+            let rest = &contents[pos + attr.len()..];
+            let suffix = &rest[..rest.find('"')?];
+            let is_disambiguator = suffix.is_empty()
+                || suffix
+                    .strip_prefix('-')
+                    .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+            if !is_disambiguator {
+                return None;
+            }
+            let imp = contents[..pos].rfind(r#"id="impl-"#).and_then(|i| {
+                let s = &contents[i + 4..];
+                s.find('"').map(|end| s[..end].to_owned())
+            });
+            Some((imp, format!("{}{}", id, suffix)))
+        })
+        .collect()
+}
+
+/// Ids are like `impl-Debug`, `impl-Debug-for-Widget` or `impl-From%3CT%3E-for-Widget`
+fn is_impl_of(impl_id: &str, trait_name: &str) -> bool {
+    impl_id
+        .strip_prefix("impl-")
+        .and_then(|s| s.strip_prefix(trait_name))
+        .is_some_and(|s| s.is_empty() || s.starts_with('-') || s.starts_with("%3C"))
+}
+
+/// Removes `..` lexically
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            c => normalized.push(c)
+        }
+    }
+    normalized
+}
+
+/// Inverts [`item_file`] into the path and the kind of the item
+pub fn item_from_page(doc_dir: &Path, file: &Path, id: Option<&str>) -> Option<(String, ItemType)> {
+    let rel = file.strip_prefix(doc_dir).ok()?;
+    let mut components = rel.iter().map(|c| c.to_str()).collect::<Option<Vec<_>>>()?;
+    let page = components.pop()?;
+    let (mut path, mut ty) = if page == "index.html" {
+        (components.join("::"), ItemType::Module)
+    } else {
+        let (ty, name) = page.strip_suffix(".html")?.split_once('.')?;
+        components.push(name);
+        (components.join("::"), ItemType::from_str(ty).ok()?)
+    };
+    if let Some(id) = id {
+        let (kind, name) = id.split_once('.')?;
+        // Strips the disambiguator of rustdoc such as `method.fmt-1`
+        let name = name.rsplit_once('-').map_or(name, |(n, _)| n);
+        match name.split_once(".field.") {
+            Some((variant, field)) if kind == "variant" => {
+                path = format!("{}::{}::{}", path, variant, field);
+                ty = ItemType::StructField;
+            }
+            _ => {
+                path = format!("{}::{}", path, name);
+                ty = ItemType::from_str(kind).ok()?;
+            }
+        }
+    }
+    Some((path, ty))
+}
+
+/// The page relative to the doc dir and the anchor, as search.js of rustdoc links them
+pub fn item_file(item: &Item) -> (PathBuf, Option<String>) {
+    let dir: PathBuf = item.path.split("::").collect();
+    match &item.parent {
+        Some((ParentType::Variant, variant)) => {
+            // The path of a variant field ends with the enum
+            let enum_name = dir.file_name().unwrap_or_default().to_string_lossy();
+            let file = dir.with_file_name(format!("enum.{}.html", enum_name));
+            let id = if is_tuple_field(&item.name) {
+                format!("variant.{}", variant)
+            } else {
+                format!("variant.{}.field.{}", variant, item.name)
+            };
+            (file, Some(id))
+        }
+        Some((parent_ty, parent)) => {
+            let parent_ty = ItemType::from(*parent_ty);
+            let file = dir.join(format!("{}.{}.html", parent_ty.as_str(), parent));
+            let id = (item.ty != ItemType::StructField || !is_tuple_field(&item.name))
+                .then(|| format!("{}.{}", item.ty.as_str(), item.name));
+            (file, id)
+        }
+        None => match item.ty {
+            ItemType::Module => (dir.join(&item.name).join("index.html"), None),
+            ItemType::ExternCrate => (Path::new(&item.name).join("index.html"), None),
+            ItemType::Import => (
+                dir.join("index.html"),
+                Some(format!("reexport.{}", item.name))
+            ),
+            ty => (
+                dir.join(format!("{}.{}.html", ty.as_str(), item.name)),
+                None
+            )
+        }
+    }
+}
+
+#[inline]
+fn is_tuple_field(name: &str) -> bool { name.parse::<i32>().is_ok() }
+
 fn find(
     search_index: &Path,
     krate_name: &str,
     tail: &[&str],
     ty: ItemType
-) -> Result<String, Error> {
+) -> Result<(PathBuf, Option<String>), Error> {
     let doc_dir: &Path = search_index.parent().unwrap();
     let krate_dir: PathBuf = cd_krate_dir(doc_dir, krate_name)?;
     if krate_name != "std" && krate_name != "core" {
         let (file, rest) = find_file(&krate_dir, tail, ty).ok_or(LocationError::FileNotFound)?;
-        let url = item_url(&file, rest, ty);
-        return Ok(url);
+        return Ok((file, item_id(rest, ty)?));
     }
     let (file, rest) = match tail.len() {
         1 if ty == ItemType::Primitive && STD_PRIMITIVES.iter().any(|p| *p == tail[0]) => {
@@ -52,22 +664,50 @@ fn find(
         }
         _ => find_file(&krate_dir, tail, ty).ok_or(LocationError::FileNotFound)?
     };
-    let url = item_url(&file, rest, ty);
-    Ok(url)
+    Ok((file, item_id(rest, ty)?))
 }
 
-fn parse_line(line: &str) -> Result<(Vec<&str>, ItemType), Error> {
-    let (fst, snd) = {
-        let mut a = line.split_whitespace();
-        a.next()
-            .and_then(|fst| a.next().map(|snd| (fst, snd)))
-            .ok_or(LocationError::InvalidFormat)
-    }?;
-    let ty = ItemType::from_str(snd).map_err(LocationError::from)?;
+/// A line of list or a path without the kind
+fn parse_query(line: &str) -> Result<(Vec<&str>, Option<ItemType>), Error> {
+    let mut a = line.split_whitespace();
+    let fst = a.next().ok_or(LocationError::InvalidFormat)?;
+    let ty = a
+        .next()
+        .map(ItemType::from_str)
+        .transpose()
+        .map_err(LocationError::from)?;
     let path_components = fst.split("::").collect::<Vec<_>>();
     Ok((path_components, ty))
 }
 
+/// Types first, members of types last
+fn kind_priority(ty: ItemType) -> u8 {
+    match ty {
+        ItemType::Struct
+        | ItemType::Enum
+        | ItemType::Union
+        | ItemType::Trait
+        | ItemType::Typedef
+        | ItemType::Primitive
+        | ItemType::TraitAlias
+        | ItemType::ForeignType => 0,
+        ItemType::Function
+        | ItemType::Macro
+        | ItemType::Constant
+        | ItemType::Static
+        | ItemType::ProcAttribute
+        | ItemType::ProcDerive => 1,
+        ItemType::Module | ItemType::Keyword | ItemType::ExternCrate => 2,
+        ItemType::Method
+        | ItemType::TyMethod
+        | ItemType::AssocType
+        | ItemType::AssocConst
+        | ItemType::Variant
+        | ItemType::StructField => 3,
+        _ => 4
+    }
+}
+
 fn split_krate<'a, 'b>(
     path_components: &'a [&'b str]
 ) -> Result<(&'b str, &'a [&'b str]), LocationError> {
@@ -77,18 +717,8 @@ fn split_krate<'a, 'b>(
     Ok((krate_name, &path_components[1..]))
 }
 
-fn find_search_index(krate_name: &str, current_dir: Option<PathBuf>) -> Result<PathBuf, Error> {
-    let search_index: PathBuf = if is_std_krate(krate_name) {
-        crate::search_index::find_std()
-    } else {
-        crate::search_index::find_local(current_dir)
-    }?
-    .ok_or(LocationError::DocNotFound)?;
-    Ok(search_index)
-}
-
 #[inline]
-fn is_std_krate(name: &str) -> bool { STD_CRATES.iter().any(|c| *c == name) }
+pub fn is_std_krate(name: &str) -> bool { STD_CRATES.contains(&name) }
 
 fn cd_krate_dir(doc_dir: &Path, krate_name: &str) -> Result<PathBuf, LocationError> {
     let krate_dir: PathBuf = Some(doc_dir.join(krate_name))
@@ -141,36 +771,22 @@ fn step_into_module<'a, 'b>(
     (cd, rest)
 }
 
-fn item_url(file: &Path, rest: &[&str], ty: ItemType) -> String {
-    if let Some(id) = item_id(rest, ty) {
-        format!("file://{}#{}", file.display(), id)
-    } else {
-        format!("file://{}", file.display())
-    }
-}
-
-fn item_id(rest: &[&str], ty: ItemType) -> Option<String> {
-    if rest.is_empty() {
-        return None;
-    }
-    if ty == ItemType::Import {
-        return Some(format!("reexport.{}", rest[0]));
-    }
-    if rest.len() == 1 {
-        return if ty == ItemType::StructField && rest[0].parse::<i32>().is_ok() {
-            None
-        } else {
-            Some(format!("{}.{}", ty.as_str(), rest[0]))
-        };
-    }
-    if rest.len() == 2 && ty == ItemType::StructField {
-        return if rest[1].parse::<i32>().is_ok() {
-            Some(format!("variant.{}", rest[0]))
-        } else {
-            Some(format!("variant.{}.field.{}", rest[0], rest[1]))
-        };
+/// `rest` is the path left after the page
+fn item_id(rest: &[&str], ty: ItemType) -> Result<Option<String>, LocationError> {
+    match rest {
+        [] => Ok(None),
+        [name, ..] if ty == ItemType::Import => Ok(Some(format!("reexport.{}", name))),
+        [name] if ty == ItemType::StructField && is_tuple_field(name) => Ok(None),
+        [name] => Ok(Some(format!("{}.{}", ty.as_str(), name))),
+        [variant, field] if ty == ItemType::StructField && is_tuple_field(field) => {
+            Ok(Some(format!("variant.{}", variant)))
+        }
+        [variant, field] if ty == ItemType::StructField => {
+            Ok(Some(format!("variant.{}.field.{}", variant, field)))
+        }
+        // The page found by probing does not contain the item
+        _ => Err(LocationError::ItemNotFound)
     }
-    unreachable!()
 }
 
 #[cfg(test)]
@@ -186,9 +802,16 @@ mod tests {
     async fn item_exists_for_every_line() {
         env_logger::builder().is_test(true).try_init().ok();
         let mut source = source();
-        let search_indexes = crate::search_index::search_indexes(None).await.unwrap();
+        let search_indexes = crate::search_index::search_indexes(Manifest::default())
+            .await
+            .unwrap();
+        let mut resolver = Resolver::default();
         for line in list(&mut source) {
             let line = line.unwrap();
+            if crate::book::is_section(&line) {
+                item_exists(&resolver.location(&line).unwrap());
+                continue;
+            }
             item_exists_for_every_line_impl(&search_indexes, &line, true);
         }
         if !source.wait().unwrap().success() {
@@ -196,9 +819,327 @@ mod tests {
         }
     }
 
+    /// Resolves with the index of the crate without a [`Resolver`]
+    fn resolve(
+        search_index: &Path,
+        krate_name: &str,
+        tail: &[&str],
+        ty: ItemType
+    ) -> Result<Location, Error> {
+        let items = crate::read_crate(search_index, krate_name)?.map(Crate::entries);
+        let (file, id) = resolve_with(search_index, items.as_deref(), krate_name, tail, ty)?;
+        Location::new(file, id)
+    }
+
+    fn parse_line(line: &str) -> Result<(Vec<&str>, ItemType), Error> {
+        match parse_query(line)? {
+            (path_components, Some(ty)) => Ok((path_components, ty)),
+            (_, None) => Err(LocationError::InvalidFormat.into())
+        }
+    }
+
+    #[test]
+    fn item_file_from_index() {
+        let item = |ty, path: &str, name: &str, parent: Option<(ParentType, &str)>| Item {
+            ty,
+            name: name.into(),
+            path: path.into(),
+            parent: parent.map(|(t, n)| (t, n.into())),
+            desc: String::new()
+        };
+        let cases = [
+            (
+                item(ItemType::Module, "std", "collections", None),
+                "std/collections/index.html",
+                None
+            ),
+            (
+                item(ItemType::Struct, "std::collections", "HashMap", None),
+                "std/collections/struct.HashMap.html",
+                None
+            ),
+            (
+                item(
+                    ItemType::Method,
+                    "std::collections",
+                    "insert",
+                    Some((ParentType::Struct, "HashMap"))
+                ),
+                "std/collections/struct.HashMap.html",
+                Some("method.insert")
+            ),
+            (
+                item(
+                    ItemType::Method,
+                    "core",
+                    "is_ascii",
+                    Some((ParentType::Primitive, "u8"))
+                ),
+                "core/primitive.u8.html",
+                Some("method.is_ascii")
+            ),
+            (
+                item(
+                    ItemType::StructField,
+                    "core::ops::Bound",
+                    "0",
+                    Some((ParentType::Variant, "Included"))
+                ),
+                "core/ops/enum.Bound.html",
+                Some("variant.Included")
+            ),
+            (
+                item(
+                    ItemType::StructField,
+                    "a::b::E",
+                    "x",
+                    Some((ParentType::Variant, "V"))
+                ),
+                "a/b/enum.E.html",
+                Some("variant.V.field.x")
+            ),
+            (
+                item(ItemType::Import, "a::b", "c", None),
+                "a/b/index.html",
+                Some("reexport.c")
+            )
+        ];
+        for (item, file, id) in cases.iter() {
+            let (f, i) = item_file(item);
+            assert_eq!(f, PathBuf::from(file));
+            assert_eq!(i.as_deref(), *id);
+        }
+    }
+
+    #[test]
+    fn every_line_of_synthetic_tree() {
+        let tree = crate::fixture::doc_tree();
+        let search_index = tree.search_index();
+        let krates = crate::read_search_index(&search_index)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(krates.len(), 2);
+        for (_, krate) in krates {
+            for line in krate.items() {
+                let (path_components, ty) = parse_line(&line).unwrap();
+                let (krate_name, tail) = split_krate(&path_components).unwrap();
+                let location = resolve(&search_index, krate_name, tail, ty)
+                    .unwrap_or_else(|e| panic!("{} {}", line, e));
+                assert!(
+                    location.anchor.is_some() || ty != ItemType::Method,
+                    "{}",
+                    location
+                );
+                item_exists(&location);
+            }
+        }
+    }
+
+    #[test]
+    fn unresolvable_lines_are_errors() {
+        let tree = crate::fixture::doc_tree();
+        let search_index = tree.search_index();
+        for line in [
+            "deep::outer::inner::Widget::new::extra\tmethod",
+            "deep::nowhere::Widget::new\tmethod",
+            "missing::f\tfn"
+        ] {
+            let (path_components, ty) = parse_line(line).unwrap();
+            let (krate_name, tail) = split_krate(&path_components).unwrap();
+            assert!(
+                resolve(&search_index, krate_name, tail, ty).is_err(),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn resolver_keeps_order_and_errors() {
+        let tree = crate::fixture::doc_tree();
+        let mut resolver = Resolver::for_test(tree.search_index());
+        let lines = [
+            "deep::outer::fn::free\tfn",
+            "deep::outer::free\tfn",
+            "std::vec\tmod"
+        ];
+        let urls = lines
+            .iter()
+            .map(|l| resolver.location(l))
+            .collect::<Vec<_>>();
+        assert!(urls[0].is_err());
+        assert!(urls[1]
+            .as_ref()
+            .unwrap()
+            .url
+            .as_str()
+            .ends_with("deep/outer/fn.free.html"));
+        assert!(matches!(
+            urls[2],
+            Err(Error::Location(LocationError::DocNotFound))
+        ));
+        assert_eq!(resolver.krates.len(), 1);
+    }
+
+    #[test]
+    fn lookup_bare_path() {
+        let tree = crate::fixture::doc_tree();
+        let mut resolver = Resolver::for_test(tree.search_index());
+        let line = |r: Result<Item, Error>| r.map(|i| i.line()).map_err(|e| e.to_string());
+        assert_eq!(
+            line(resolver.lookup("Widget::new")),
+            Ok("deep::outer::inner::Widget::new\tmethod".into())
+        );
+        assert_eq!(
+            line(resolver.lookup("deep::outer::free")),
+            Ok("deep::outer::free\tfn".into())
+        );
+        assert_eq!(
+            line(resolver.lookup("Choice")),
+            Ok("deep::Choice\tenum".into())
+        );
+        assert!(matches!(
+            resolver.lookup("nothing"),
+            Err(Error::Location(LocationError::ItemNotFound))
+        ));
+        assert!(resolver
+            .location("inner::Widget")
+            .unwrap()
+            .url
+            .as_str()
+            .ends_with("deep/outer/inner/struct.Widget.html"));
+    }
+
+    #[test]
+    fn lookup_ambiguous_path() {
+        let tree = crate::fixture::doc_tree();
+        let mut resolver = Resolver::for_test(tree.search_index());
+        match resolver.lookup("Widget") {
+            Err(Error::Location(LocationError::Ambiguous(lines))) => assert_eq!(
+                lines,
+                vec![
+                    "deep::outer::inner::Widget\tstruct".to_string(),
+                    "other::Widget\tstruct".into()
+                ]
+            ),
+            r => panic!("{:?}", r)
+        }
+    }
+
+    #[test]
+    fn follow_reexports() {
+        let tree = crate::fixture::doc_tree();
+        let search_index = tree.search_index();
+        let mut resolver = Resolver::for_test(search_index.clone());
+        let doc_dir = tree.doc_dir();
+        let location = |f: &str, id: Option<&str>| {
+            Location::new(doc_dir.join(f), id.map(str::to_owned)).unwrap()
+        };
+        assert_eq!(
+            resolver.location("deep::Shape\timport").unwrap(),
+            location("deep/outer/inner/trait.Shape.html", None)
+        );
+        assert_eq!(
+            resolver
+                .location("deep::outer::Widget::new\tmethod")
+                .unwrap(),
+            location("deep/outer/inner/struct.Widget.html", Some("method.new"))
+        );
+        assert_eq!(
+            resolver.definition("deep::outer::Widget\tstruct").unwrap(),
+            (
+                "deep::outer::Widget".to_string(),
+                "deep::outer::inner::Widget".to_string()
+            )
+        );
+        assert_eq!(
+            item_from_page(
+                doc_dir,
+                &doc_dir.join("deep/enum.Choice.html"),
+                Some("variant.A.field.x")
+            ),
+            Some(("deep::Choice::A::x".to_string(), ItemType::StructField))
+        );
+    }
+
+    #[test]
+    fn anchors_of_impls() {
+        let tree = crate::fixture::doc_tree();
+        let mut resolver = Resolver::for_test(tree.search_index());
+        let line = "deep::outer::inner::Widget::fmt\tmethod";
+        let impls = resolver.impls(line).unwrap();
+        let ids = impls.iter().map(|(i, _)| i.as_deref()).collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![
+                Some("impl-Display-for-Widget"),
+                Some("impl-Debug-for-Widget")
+            ]
+        );
+        assert_eq!(
+            resolver
+                .location_in_impl(line, "fmt::Debug")
+                .unwrap()
+                .anchor
+                .as_deref(),
+            Some("method.fmt-1")
+        );
+        assert_eq!(
+            resolver
+                .location_in_impl(line, "Display")
+                .unwrap()
+                .anchor
+                .as_deref(),
+            Some("method.fmt")
+        );
+        assert!(resolver.location_in_impl(line, "Dis").is_err());
+        assert_eq!(
+            resolver
+                .impls("deep::outer::inner::Widget::new\tmethod")
+                .unwrap()[0]
+                .0,
+            None
+        );
+    }
+
+    #[test]
+    fn rewrite_to_base_url() {
+        let tree = crate::fixture::doc_tree();
+        let mut resolver = Resolver::for_test(tree.search_index())
+            .url_style(UrlStyle::Base("https://docs.example.com/".into()));
+        assert_eq!(
+            resolver
+                .location("deep::outer::inner::Widget::new\tmethod")
+                .unwrap()
+                .url
+                .as_str(),
+            "https://docs.example.com/deep/outer/inner/struct.Widget.html#method.new"
+        );
+        assert_eq!(
+            resolver.location("deep::outer\tmod").unwrap().url.as_str(),
+            "https://docs.example.com/deep/outer/index.html"
+        );
+    }
+
+    #[test]
+    fn percent_encode_file_urls() {
+        let location = Location::new(
+            PathBuf::from("/tmp/my docs#1/deep/struct.Widget.html"),
+            Some("method.new".into())
+        )
+        .unwrap();
+        assert_eq!(
+            location.url.as_str(),
+            "file:///tmp/my%20docs%231/deep/struct.Widget.html#method.new"
+        );
+        assert_eq!(location.url.to_file_path().unwrap(), location.file);
+        assert!(Location::new(PathBuf::from("deep/index.html"), None).is_err());
+    }
+
     fn source() -> Child {
         let child = Command::new("./target/debug/cargo-listdoc")
-            .args(&["listdoc", "show"])
+            .args(["listdoc", "show"])
             .stdout(Stdio::piped())
             .spawn()
             .unwrap();
@@ -216,7 +1157,7 @@ mod tests {
         log::debug!("{} {:?}", krate_name, tail);
         let maybe_file = search_indexes
             .iter()
-            .find_map(|s| find(s, krate_name, tail, ty).ok());
+            .find_map(|s| resolve(s, krate_name, tail, ty).ok());
         let file = match maybe_file {
             None => panic!("Not found {}", line),
             Some(x) => x
@@ -226,15 +1167,13 @@ mod tests {
         }
     }
 
-    fn item_exists(url: &str) {
-        let idx = match url.find('#') {
+    fn item_exists(location: &Location) {
+        let item = match &location.anchor {
             Some(x) => x,
             None => return
         };
-        log::debug!("{}", url);
-        let (file, item) = url.split_at(idx);
-        let item = &item[1..];
-        let file = file.strip_prefix("file://").unwrap();
+        log::debug!("{}", location);
+        let file = location.file.to_str().unwrap();
         let id = format!(r#"id="{}""#, item);
         let contents = std::fs::read_to_string(file).unwrap();
         if !contents.contains(&id) {
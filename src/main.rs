@@ -1,29 +1,382 @@
 use rayon::prelude::*;
-use rustdoc_index::*;
-use std::io::{stdout, BufWriter, Write};
+use rustdoc_index::{
+    search_index::{Manifest, RootKind},
+    workspace::{CrateInfo, CrateKind, Workspace},
+    *
+};
+use std::{
+    collections::{HashMap, HashSet},
+    io::{stdin, stdout, BufRead, BufWriter, Write},
+    path::{Path, PathBuf}
+};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
 struct Opt {
     #[structopt(name = "listdoc")]
     _void: String,
+    #[structopt(long, global = true, help = "Path to Cargo.toml")]
+    manifest_path: Option<PathBuf>,
+    #[structopt(short, long, global = true, help = "Package to list docs for")]
+    package: Option<String>,
     #[structopt(subcommand)]
     pub sub: Option<SubCommand>
 }
 
+impl Opt {
+    fn manifest(&self) -> Manifest {
+        Manifest {
+            current_dir: None,
+            manifest_path: self.manifest_path.clone(),
+            package: self.package.clone()
+        }
+    }
+}
+
 #[derive(Debug, StructOpt)]
 enum SubCommand {
-    Show,
-    Location(Location)
+    Show(Show),
+    Location(Location),
+    Open(Open),
+    View(View),
+    Markdown(Markdown),
+    Signature(Signature),
+    Preview(Preview),
+    Examples(Examples),
+    Source(Source),
+    Implementors(Implementors),
+    Impls(Impls),
+    #[structopt(
+        about = "List documented crates with the number of items and versions in use. The number is - when the search index is not readable, such as the search.index/ directory of newer rustdoc"
+    )]
+    Crates,
+    Files(Files),
+    Which(Which),
+    Error(Explain)
+}
+
+#[derive(Debug, Default, StructOpt)]
+struct Show {
+    #[structopt(long, help = "List only crates of workspace members")]
+    members_only: bool,
+    #[structopt(long, help = "Skip transitive dependencies")]
+    direct_deps: bool,
+    #[structopt(long, help = "Append member, direct, transitive or std")]
+    crate_kind: bool,
+    #[structopt(long, help = "Append version, source and license of the crate")]
+    crate_info: bool,
+    #[structopt(long, help = "Append the declaration of the item, for small listings")]
+    with_decl: bool,
+    #[structopt(
+        long,
+        help = "Also list chapters and headings of the books next to std docs"
+    )]
+    books: bool,
+    #[structopt(
+        long,
+        help = "List compiler error codes with their summaries instead of items",
+        conflicts_with_all = &["members-only", "direct-deps", "crate-kind", "crate-info", "with-decl", "books"]
+    )]
+    errors: bool
+}
+
+impl Show {
+    fn needs_workspace(&self) -> bool {
+        self.members_only || self.direct_deps || self.crate_kind || self.crate_info
+    }
+
+    fn accepts(&self, kind: Option<CrateKind>) -> bool {
+        if self.members_only {
+            kind == Some(CrateKind::Member)
+        } else if self.direct_deps {
+            matches!(kind, Some(k) if k != CrateKind::Transitive)
+        } else {
+            true
+        }
+    }
 }
 
 #[derive(Debug, StructOpt)]
 struct Location {
+    #[structopt(name = "line", required_unless = "stdin")]
+    #[structopt(help = "A line of list, or a path such as HashMap::insert")]
+    line: Option<String>,
+    #[structopt(long, help = "Resolve every line of stdin in one process")]
+    stdin: bool,
+    #[structopt(
+        long,
+        help = "Print the public path and the path where the item is defined before the url"
+    )]
+    definition: bool,
+    #[structopt(long = "impl", help = "Open the item in the impl of this trait")]
+    trait_name: Option<String>,
+    #[structopt(
+        long,
+        conflicts_with = "stdin",
+        help = "List the item of every impl block on the page, from its anchors since the index \
+                does not record the trait; without --impl the first one is used"
+    )]
+    impls: bool,
+    #[structopt(flatten)]
+    url_style: UrlStyle
+}
+
+#[derive(Debug, StructOpt)]
+struct UrlStyle {
+    #[structopt(
+        long,
+        default_value = "file",
+        possible_values = &["file", "web", "base"],
+        help = "Print installed pages, doc.rust-lang.org and docs.rs pages, or pages under --base-url"
+    )]
+    url_style: String,
+    #[structopt(
+        long,
+        required_if("url-style", "base"),
+        help = "The url serving the doc dir"
+    )]
+    base_url: Option<String>
+}
+
+impl UrlStyle {
+    fn style(&self) -> location::UrlStyle {
+        match self.url_style.as_str() {
+            "web" => location::UrlStyle::Web,
+            "base" => location::UrlStyle::Base(self.base_url.clone().unwrap_or_default()),
+            _ => location::UrlStyle::File
+        }
+    }
+}
+
+impl Location {
+    fn resolve(&self, resolver: &mut location::Resolver, line: &str) -> Result<String, Error> {
+        if self.impls {
+            let impls = resolver.impls(line)?;
+            let lines = impls
+                .into_iter()
+                .map(|(imp, location)| {
+                    format!("{}\t{}", imp.as_deref().unwrap_or("-"), location.url)
+                })
+                .collect::<Vec<_>>();
+            return Ok(lines.join("\n"));
+        }
+        let url = match &self.trait_name {
+            Some(trait_name) => resolver.location_in_impl(line, trait_name)?,
+            None => resolver.location(line)?
+        }
+        .url;
+        if !self.definition {
+            return Ok(url.into());
+        }
+        let (public, definition) = resolver.definition(line)?;
+        Ok(format!("{}\t{}\t{}", public, definition, url))
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct Open {
+    #[structopt(name = "line", required_unless = "stdin")]
+    #[structopt(help = "A line of list, or a path such as HashMap::insert")]
+    line: Option<String>,
+    #[structopt(long, help = "Open every line of stdin")]
+    stdin: bool,
+    #[structopt(long = "impl", help = "Open the item in the impl of this trait")]
+    trait_name: Option<String>,
+    #[structopt(flatten)]
+    url_style: UrlStyle,
+    #[structopt(long, help = "Print the command instead of running it")]
+    print: bool,
+    #[structopt(
+        long,
+        help = "The command to open urls, with %s for the url if not last"
+    )]
+    browser: Option<String>
+}
+
+impl Open {
+    fn resolve(
+        &self,
+        resolver: &mut location::Resolver,
+        line: &str
+    ) -> Result<location::Location, Error> {
+        locate(resolver, line, self.trait_name.as_deref())
+    }
+
+    /// Prints the command when the opener is not resolved for `--print`
+    fn open(
+        &self,
+        opener: Option<&open::Opener>,
+        location: &location::Location
+    ) -> Result<(), Error> {
+        match opener {
+            Some(opener) => opener.open(location.url.as_str()),
+            None => {
+                let planned = open::Opener::planned(self.browser.as_deref());
+                println!("{}", planned.command_line(location.url.as_str()).join(" "));
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct View {
     #[structopt(name = "line")]
-    #[structopt(help = "A line of list")]
+    #[structopt(help = "A line of list, or a path such as HashMap::insert")]
+    line: String,
+    #[structopt(long = "impl", help = "View the item in the impl of this trait")]
+    trait_name: Option<String>,
+    #[structopt(long, help = "Wrap at this width instead of $COLUMNS")]
+    width: Option<usize>
+}
+
+impl View {
+    fn style(&self) -> view::Style { terminal_style(self.width) }
+}
+
+/// Wraps at $COLUMNS and colors only a terminal
+fn terminal_style(width: Option<usize>) -> view::Style {
+    use std::io::IsTerminal;
+    let columns = std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok());
+    view::Style {
+        width: width.or(columns).unwrap_or(80),
+        color: stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct Markdown {
+    #[structopt(name = "line")]
+    #[structopt(help = "A line of list, or a path such as HashMap::insert")]
+    line: String,
+    #[structopt(long = "impl", help = "Convert the item in the impl of this trait")]
+    trait_name: Option<String>,
+    #[structopt(
+        long,
+        default_value = "path",
+        possible_values = &["path", "url"],
+        help = "Rewrite links to item paths or to urls in --url-style"
+    )]
+    links: String,
+    #[structopt(long, help = "Append the signatures of methods")]
+    methods: bool,
+    #[structopt(flatten)]
+    url_style: UrlStyle
+}
+
+#[derive(Debug, StructOpt)]
+struct Signature {
+    #[structopt(name = "line", required_unless = "stdin")]
+    #[structopt(help = "A line of list, or a path such as HashMap::insert")]
+    line: Option<String>,
+    #[structopt(
+        long,
+        help = "Print the declaration of every line of stdin on one line"
+    )]
+    stdin: bool,
+    #[structopt(long = "impl", help = "The item in the impl of this trait")]
+    trait_name: Option<String>
+}
+
+#[derive(Debug, StructOpt)]
+struct Preview {
+    #[structopt(name = "line")]
+    #[structopt(help = "A line of list, or a path such as HashMap::insert")]
+    line: String,
+    #[structopt(long, help = "Wrap at this width instead of $FZF_PREVIEW_COLUMNS")]
+    width: Option<usize>,
+    #[structopt(long, help = "Neither read nor write the cache of previews")]
+    no_cache: bool
+}
+
+impl Preview {
+    fn style(&self) -> view::Style {
+        let columns = ["FZF_PREVIEW_COLUMNS", "COLUMNS"]
+            .iter()
+            .find_map(|v| std::env::var(v).ok()?.parse().ok());
+        view::Style {
+            width: self.width.or(columns).unwrap_or(80),
+            // fzf passes colors through though the preview is not a terminal
+            color: std::env::var_os("NO_COLOR").is_none()
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct Examples {
+    #[structopt(name = "line")]
+    #[structopt(help = "A line of list, or a path such as HashMap::insert")]
+    line: String,
+    #[structopt(long = "impl", help = "The item in the impl of this trait")]
+    trait_name: Option<String>,
+    #[structopt(
+        long,
+        parse(from_os_str),
+        help = "Write a cargo project with a binary per example into this directory"
+    )]
+    scaffold: Option<PathBuf>
+}
+
+#[derive(Debug, StructOpt)]
+struct Source {
+    #[structopt(name = "line")]
+    #[structopt(help = "A line of list, or a path such as HashMap::insert")]
+    line: String,
+    #[structopt(long = "impl", help = "The item in the impl of this trait")]
+    trait_name: Option<String>,
+    #[structopt(long, help = "Print file:line to jump to in an editor")]
+    path_only: bool
+}
+
+#[derive(Debug, StructOpt)]
+struct Implementors {
+    #[structopt(name = "line")]
+    #[structopt(help = "A line of list of a trait, or a path such as serde::Serialize")]
+    line: String,
+    #[structopt(long, help = "List only implementors in crates of workspace members")]
+    members_only: bool,
+    #[structopt(
+        long,
+        help = "Skip auto trait and blanket impls that rustdoc synthesizes"
+    )]
+    no_synthetic: bool
+}
+
+#[derive(Debug, StructOpt)]
+struct Impls {
+    #[structopt(name = "line")]
+    #[structopt(help = "A line of list of a type, or a path such as HashMap")]
     line: String
 }
 
+#[derive(Debug, StructOpt)]
+struct Files {
+    #[structopt(long = "crate", help = "List only files of this crate")]
+    krate: Option<String>
+}
+
+#[derive(Debug, StructOpt)]
+struct Explain {
+    #[structopt(name = "code")]
+    #[structopt(help = "A code such as E0308, or a line of show --errors")]
+    code: String,
+    #[structopt(long, help = "Render the explanation instead of printing the url")]
+    view: bool,
+    #[structopt(long, help = "Wrap at this width instead of $COLUMNS")]
+    width: Option<usize>,
+    #[structopt(flatten)]
+    url_style: UrlStyle
+}
+
+#[derive(Debug, StructOpt)]
+struct Which {
+    #[structopt(name = "url")]
+    #[structopt(help = "A url or a path of a page of docs")]
+    target: String,
+    #[structopt(long, help = "Print as JSON")]
+    json: bool
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Error> {
     let opt: Opt = Opt::from_args();
@@ -31,32 +384,496 @@ async fn main() -> Result<(), Error> {
 }
 
 async fn run(opt: Opt) -> Result<(), Error> {
-    match opt.sub.unwrap_or(SubCommand::Show) {
-        SubCommand::Show => list().await,
-        SubCommand::Location(args) => location(args).await
+    let manifest = opt.manifest();
+    match opt.sub.unwrap_or_else(|| SubCommand::Show(Show::default())) {
+        SubCommand::Show(args) => list(args, manifest).await,
+        SubCommand::Location(args) => location(args, manifest).await,
+        SubCommand::Open(args) => open(args, manifest),
+        SubCommand::View(args) => view(args, manifest),
+        SubCommand::Markdown(args) => markdown(args, manifest),
+        SubCommand::Signature(args) => signature(args, manifest),
+        SubCommand::Preview(args) => preview(args, manifest),
+        SubCommand::Examples(args) => examples(args, manifest),
+        SubCommand::Source(args) => source(args, manifest),
+        SubCommand::Implementors(args) => implementors(args, manifest),
+        SubCommand::Impls(args) => impls(args, manifest),
+        SubCommand::Crates => crates(manifest).await,
+        SubCommand::Files(args) => files(args, manifest).await,
+        SubCommand::Which(args) => which(args, manifest),
+        SubCommand::Error(args) => explain(args, manifest)
     }
 }
 
-async fn list() -> Result<(), Error> {
-    for search_index in search_index::search_indexes(None).await?.into_iter() {
-        let doc = read_search_index(search_index)?;
+async fn list(args: Show, manifest: Manifest) -> Result<(), Error> {
+    if args.errors {
+        return list_errors();
+    }
+    let workspace = if args.needs_workspace() || manifest.package.is_some() {
+        let manifest = manifest.clone();
+        match tokio::spawn(async move { Workspace::load(&manifest) }).await? {
+            Ok(workspace) => workspace,
+            // The scope of --package only narrows the listing
+            Err(e @ Error::Metadata(_)) if !args.needs_workspace() => {
+                eprintln!("Listing every crate without the scope of --package: {}", e);
+                Workspace::default()
+            }
+            Err(e) => return Err(e)
+        }
+    } else {
+        Workspace::default()
+    };
+    let roots = search_index::search_index_roots(manifest).await?;
+    for (root, search_index) in roots.into_iter() {
+        let kind_of = |name: &str| match root {
+            RootKind::Std => Some(CrateKind::Std),
+            RootKind::Local => workspace.kind(name)
+        };
+        // Filters by crates.js before parsing the index, such as std for --members-only
+        let keep = search_index::crate_names(&search_index)?
+            .into_iter()
+            .filter(|name| {
+                (root != RootKind::Local || workspace.in_scope(name)) && args.accepts(kind_of(name))
+            })
+            .collect::<HashSet<_>>();
+        if keep.is_empty() {
+            continue;
+        }
+        let doc = read_search_index_of(&search_index, |name| keep.contains(name))?;
         doc.try_for_each(|r: Result<(String, doc::Crate), Error>| -> Result<(), _> {
             let out = stdout();
             let mut out = BufWriter::new(out.lock());
-            r.and_then(|(_name, krate)| -> Result<_, _> {
-                for path in krate.items() {
-                    writeln!(out, "{}", path)?;
+            r.and_then(|(name, krate)| -> Result<_, _> {
+                let kind = kind_of(&name);
+                let mut columns = String::new();
+                if args.crate_kind {
+                    columns.push('\t');
+                    columns.push_str(kind.map_or("unknown", |k| k.as_str()));
+                }
+                if args.crate_info {
+                    columns.push_str(&crate_info(workspace.info(&name)));
+                }
+                if !args.with_decl {
+                    for path in krate.items() {
+                        writeln!(out, "{}{}", path, columns)?;
+                    }
+                    return Ok(());
+                }
+                let doc_dir = search_index.parent().unwrap_or(Path::new("."));
+                let mut signatures = signature::Signatures::default();
+                for item in krate.entries() {
+                    let decl = signatures
+                        .of_item(doc_dir, &item)
+                        .map_or_else(|| "-".into(), |d| signature::one_line(&d));
+                    writeln!(out, "{}{}\t{}", item.line(), columns, decl)?;
                 }
                 Ok(())
             })
         })
         .unwrap();
     }
+    if args.books && args.accepts(Some(CrateKind::Std)) {
+        list_books(&args)?;
+    }
+    Ok(())
+}
+
+/// Sections of the books next to std docs, with the columns of std crates
+fn list_books(args: &Show) -> Result<(), Error> {
+    let doc_dir = match search_index::find_std_doc_dir()? {
+        Some(dir) => dir,
+        None => return Ok(())
+    };
+    let mut columns = String::new();
+    if args.crate_kind {
+        columns.push('\t');
+        columns.push_str(CrateKind::Std.as_str());
+    }
+    if args.crate_info {
+        columns.push_str(&crate_info(None));
+    }
+    if args.with_decl {
+        columns.push_str("\t-");
+    }
+    book::read_books(&doc_dir)?.try_for_each(|r| -> Result<(), Error> {
+        let out = stdout();
+        let mut out = BufWriter::new(out.lock());
+        for section in r?.1 {
+            writeln!(out, "{}{}", section.line(), columns)?;
+        }
+        Ok(())
+    })
+}
+
+/// Error codes of the error index next to std docs
+fn list_errors() -> Result<(), Error> {
+    let doc_dir = match search_index::find_std_doc_dir()? {
+        Some(dir) => dir,
+        None => return Ok(())
+    };
+    let out = stdout();
+    let mut out = BufWriter::new(out.lock());
+    for code in errors::error_codes(&doc_dir)? {
+        writeln!(out, "{}\t{}", code.line(), code.summary)?;
+    }
     Ok(())
 }
 
-async fn location(args: Location) -> Result<(), Error> {
-    let url = location::location_from_line(&args.line, None).await?;
-    println!("{}", url);
+/// Version, source and license of the package documented for the crate
+fn crate_info(info: Option<&CrateInfo>) -> String {
+    match info {
+        Some(i) => format!(
+            "\t{}\t{}\t{}",
+            i.version,
+            i.source.as_str(),
+            i.license.as_deref().unwrap_or("-")
+        ),
+        None => "\t-\t-\t-".into()
+    }
+}
+
+/// The location of the item, or of the item in the impl of the trait
+fn locate(
+    resolver: &mut location::Resolver,
+    line: &str,
+    trait_name: Option<&str>
+) -> Result<location::Location, Error> {
+    match trait_name {
+        Some(trait_name) => resolver.location_in_impl(line, trait_name),
+        None => resolver.location(line)
+    }
+}
+
+/// Prints the candidates of an ambiguous path and exits with 1
+fn exit_if_ambiguous<T>(r: Result<T, Error>) -> Result<T, Error> {
+    if let Err(Error::Location(location::LocationError::Ambiguous(candidates))) = &r {
+        for c in candidates {
+            println!("{}", c);
+        }
+        std::process::exit(1);
+    }
+    r
+}
+
+async fn location(args: Location, manifest: Manifest) -> Result<(), Error> {
+    let mut resolver = location::Resolver::new(manifest).url_style(args.url_style.style());
+    if let Some(line) = args.line.as_deref().filter(|_| !args.stdin) {
+        println!("{}", exit_if_ambiguous(args.resolve(&mut resolver, line))?);
+        return Ok(());
+    }
+    let out = stdout();
+    let mut out = out.lock();
+    for line in stdin().lock().lines() {
+        let line = line?;
+        match args.resolve(&mut resolver, &line) {
+            Ok(x) => writeln!(out, "{}", x)?,
+            Err(e) => {
+                // An empty line keeps the output aligned with the input
+                eprintln!("{}: {}", line, e);
+                writeln!(out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn open(args: Open, manifest: Manifest) -> Result<(), Error> {
+    // A dry run does not need a command that exists
+    let opener = if args.print {
+        None
+    } else {
+        Some(open::Opener::find(args.browser.as_deref())?)
+    };
+    let mut resolver = location::Resolver::new(manifest).url_style(args.url_style.style());
+    if let Some(line) = args.line.as_deref().filter(|_| !args.stdin) {
+        let location = exit_if_ambiguous(args.resolve(&mut resolver, line))?;
+        return args.open(opener.as_ref(), &location);
+    }
+    for line in stdin().lock().lines() {
+        let line = line?;
+        if let Err(e) = args
+            .resolve(&mut resolver, &line)
+            .and_then(|location| args.open(opener.as_ref(), &location))
+        {
+            eprintln!("{}: {}", line, e);
+        }
+    }
+    Ok(())
+}
+
+fn view(args: View, manifest: Manifest) -> Result<(), Error> {
+    let mut resolver = location::Resolver::new(manifest);
+    let location = exit_if_ambiguous(locate(
+        &mut resolver,
+        &args.line,
+        args.trait_name.as_deref()
+    ))?;
+    view::page(&view::view(&location, args.style())?)
+}
+
+fn markdown(args: Markdown, manifest: Manifest) -> Result<(), Error> {
+    let mut resolver = location::Resolver::new(manifest).url_style(args.url_style.style());
+    let location = exit_if_ambiguous(locate(
+        &mut resolver,
+        &args.line,
+        args.trait_name.as_deref()
+    ))?;
+    let links = match args.links.as_str() {
+        "url" => markdown::Links::Url,
+        _ => markdown::Links::Path
+    };
+    print!("{}", markdown::markdown(&location, links, args.methods)?);
+    Ok(())
+}
+
+fn signature(args: Signature, manifest: Manifest) -> Result<(), Error> {
+    let mut resolver = location::Resolver::new(manifest);
+    let mut signature = |line: &str| {
+        locate(&mut resolver, line, args.trait_name.as_deref())
+            .and_then(|location| signature::signature(&location))
+    };
+    if let Some(line) = args.line.as_deref().filter(|_| !args.stdin) {
+        println!("{}", exit_if_ambiguous(signature(line))?);
+        return Ok(());
+    }
+    let out = stdout();
+    let mut out = out.lock();
+    for line in stdin().lock().lines() {
+        let line = line?;
+        match signature(&line) {
+            Ok(decl) => writeln!(out, "{}", signature::one_line(&decl))?,
+            Err(e) => {
+                eprintln!("{}: {}", line, e);
+                writeln!(out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn preview(args: Preview, manifest: Manifest) -> Result<(), Error> {
+    let style = args.style();
+    // Local docs differ between projects
+    let key = format!(
+        "{}\t{}\t{}\t{:?}\t{:?}\t{:?}",
+        args.line,
+        style.width,
+        style.color,
+        std::env::current_dir().ok(),
+        manifest.manifest_path,
+        manifest.package
+    );
+    let cache = preview::Cache::new().filter(|_| !args.no_cache);
+    if let Some(text) = cache.as_ref().and_then(|c| c.get(&key)) {
+        print!("{}", text);
+        return Ok(());
+    }
+    let mut resolver = location::Resolver::new(manifest.clone());
+    // Sections of books and error codes are not items
+    let (file, text) = if book::is_section(&args.line) || errors::is_error(&args.line) {
+        let location = resolver.location(&args.line)?;
+        let text = view::view(&location, style)?;
+        (location.file, text)
+    } else {
+        let preview = exit_if_ambiguous(preview::Preview::new(&mut resolver, &args.line))?;
+        let text = preview.render(style);
+        (preview.file, text)
+    };
+    if let Some(cache) = &cache {
+        cache.put(&key, &file, &text);
+    }
+    print!("{}", text);
+    Ok(())
+}
+
+fn examples(args: Examples, manifest: Manifest) -> Result<(), Error> {
+    let mut resolver = location::Resolver::new(manifest.clone());
+    let item = exit_if_ambiguous(resolver.item(&args.line))?;
+    let location = locate(&mut resolver, &item.line(), args.trait_name.as_deref())?;
+    let contents = std::fs::read_to_string(&location.file)?;
+    let fragment = view::docblock(&contents, location.anchor.as_deref())
+        .ok_or(location::LocationError::ItemNotFound)?;
+    let found = examples::examples(&fragment);
+    let dir = match args.scaffold {
+        Some(dir) => dir,
+        None => {
+            for (i, example) in found.iter().enumerate() {
+                let attrs = if example.attrs.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", example.attrs.join(", "))
+                };
+                println!("// Example {}{}\n{}\n", i + 1, attrs, example.code);
+            }
+            return Ok(());
+        }
+    };
+    let krate_name = item.path.split("::").next().unwrap_or_default();
+    let dependency = if location::is_std_krate(krate_name) {
+        None
+    } else {
+        let dependency = examples::dependency(krate_name, &Workspace::load(&manifest)?);
+        if dependency.is_none() {
+            eprintln!("{} is not in the dependency graph", krate_name);
+        }
+        dependency
+    };
+    let name = format!("{}-examples", item.name.to_lowercase().replace('_', "-"));
+    for file in examples::scaffold(&dir, &name, dependency.as_deref(), &found)? {
+        println!("{}", file.display());
+    }
+    Ok(())
+}
+
+fn source(args: Source, manifest: Manifest) -> Result<(), Error> {
+    let mut resolver = location::Resolver::new(manifest.clone());
+    let location = exit_if_ambiguous(locate(
+        &mut resolver,
+        &args.line,
+        args.trait_name.as_deref()
+    ))?;
+    let source = source::Source::new(&location)?;
+    let src_dir = if location::is_std_krate(&source.krate) {
+        search_index::find_rust_src()?.map(|lib| lib.join(&source.krate).join("src"))
+    } else {
+        workspace_or_default(&manifest)
+            .info(&source.krate)
+            .map(|i| i.src_dir.clone())
+    };
+    let original = src_dir.and_then(|d| source.original(&d));
+    if args.path_only {
+        let file = original.as_deref().unwrap_or(&source.page);
+        println!("{}:{}", file.display(), source.first);
+    } else {
+        print!("{}", source.text(original.as_deref())?);
+    }
+    Ok(())
+}
+
+fn implementors(args: Implementors, manifest: Manifest) -> Result<(), Error> {
+    let mut resolver = location::Resolver::new(manifest.clone());
+    let found = exit_if_ambiguous(implementors::of_trait(&mut resolver, &args.line))?;
+    let workspace = if args.members_only {
+        Some(Workspace::load(&manifest)?)
+    } else {
+        None
+    };
+    let out = stdout();
+    let mut out = BufWriter::new(out.lock());
+    for imp in found {
+        if args.no_synthetic && imp.synthetic {
+            continue;
+        }
+        if let Some(w) = &workspace {
+            if w.kind(&imp.krate) != Some(CrateKind::Member) {
+                continue;
+            }
+        }
+        // Blanket impls have no type to list
+        let line = imp.line().unwrap_or_else(|| "-\t-".into());
+        // Types of crates without local docs have no page
+        let url = match imp.page {
+            Some(page) => location::Location::new(page, None)?.to_string(),
+            None => "-".into()
+        };
+        writeln!(out, "{}\t{}\t{}", line, imp.header, url)?;
+    }
+    Ok(())
+}
+
+fn impls(args: Impls, manifest: Manifest) -> Result<(), Error> {
+    let mut resolver = location::Resolver::new(manifest);
+    let found = exit_if_ambiguous(impls::of_type(&mut resolver, &args.line))?;
+    let out = stdout();
+    let mut out = BufWriter::new(out.lock());
+    for imp in found {
+        let location = location::Location::new(imp.page, imp.anchor)?;
+        writeln!(out, "{}\t{}\t{}", imp.kind.as_str(), imp.header, location)?;
+    }
+    Ok(())
+}
+
+/// The dependency graph for optional details, reporting a failed resolve instead of hiding it
+fn workspace_or_default(manifest: &Manifest) -> Workspace {
+    Workspace::load(manifest).unwrap_or_else(|e| {
+        eprintln!("Going on without the dependency graph: {}", e);
+        Workspace::default()
+    })
+}
+
+/// Crates per doc dir with the number of items and the versions in the dependency graph
+async fn crates(manifest: Manifest) -> Result<(), Error> {
+    let workspace = workspace_or_default(&manifest);
+    let out = stdout();
+    let mut out = BufWriter::new(out.lock());
+    for (root, doc_dir) in search_index::doc_roots(manifest).await? {
+        let search_index = search_index::ls_search_index(&doc_dir)?;
+        let names = match (search_index::read_crates_js(&doc_dir)?, &search_index) {
+            (Some(names), _) => names,
+            (None, Some(search_index)) => search_index::crate_names(search_index)?,
+            (None, None) => continue
+        };
+        let counts = match &search_index {
+            Some(search_index) => read_search_index(search_index)?
+                .map(|r| r.map(|(name, krate)| (name, krate.entries().len())))
+                .collect::<Result<HashMap<_, _>, Error>>()?,
+            None => HashMap::new()
+        };
+        for name in names {
+            let count = counts
+                .get(&name)
+                .map_or_else(|| "-".into(), usize::to_string);
+            let infos = workspace.infos(&name);
+            let version = if infos.is_empty() {
+                "-".into()
+            } else {
+                infos
+                    .iter()
+                    .map(|i| i.version.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            };
+            writeln!(out, "{}\t{}\t{}\t{}", name, root.as_str(), count, version)?;
+        }
+    }
+    Ok(())
+}
+
+async fn files(args: Files, manifest: Manifest) -> Result<(), Error> {
+    let out = stdout();
+    let mut out = BufWriter::new(out.lock());
+    for (_, doc_dir) in search_index::doc_roots(manifest).await? {
+        for file in files::source_files(&doc_dir)? {
+            if args.krate.as_ref().is_some_and(|k| *k != file.krate) {
+                continue;
+            }
+            let location = location::Location::new(file.page, None)?;
+            writeln!(out, "{}\t{}\t{}", file.krate, file.path.display(), location)?;
+        }
+    }
+    Ok(())
+}
+
+fn explain(args: Explain, manifest: Manifest) -> Result<(), Error> {
+    let code = errors::normalize(&args.code).ok_or(location::LocationError::InvalidFormat)?;
+    let line = format!("{}\t{}", code, errors::ERROR);
+    let mut resolver = location::Resolver::new(manifest).url_style(args.url_style.style());
+    let location = resolver.location(&line)?;
+    if args.view {
+        view::page(&view::view(&location, terminal_style(args.width))?)
+    } else {
+        println!("{}", location.url);
+        Ok(())
+    }
+}
+
+fn which(args: Which, manifest: Manifest) -> Result<(), Error> {
+    let found = which::which(&args.target, &manifest)?;
+    if args.json {
+        let json =
+            serde_json::to_string(&found).map_err(|e| Error::SerdeJson(found.path.clone(), e))?;
+        println!("{}", json);
+    } else {
+        println!("{}", found.line());
+    }
     Ok(())
 }
@@ -0,0 +1,404 @@
+//! Converts docblocks of rustdoc pages back into Markdown
+use crate::{
+    html::{self, Token},
+    location::{self, Location, LocationError},
+    view, which, Error
+};
+use std::path::Path;
+
+/// What links to other items point at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Links {
+    /// Item paths such as `std::vec::Vec::push`, like intra-doc links
+    #[default]
+    Path,
+    /// Urls resolved against the url of the location
+    Url
+}
+
+/// Converts the item of the page, or the section of the anchor
+pub fn markdown(location: &Location, links: Links, methods: bool) -> Result<String, Error> {
+    let contents = std::fs::read_to_string(&location.file)?;
+    let fragment =
+        view::docblock(&contents, location.anchor.as_deref()).ok_or(LocationError::ItemNotFound)?;
+    let doc_dir = which::doc_dir_of(&location.file).ok();
+    let mut md = render(&fragment, |href| {
+        link(location, doc_dir.as_deref(), links, href)
+    });
+    if methods && location.anchor.is_none() {
+        let list = method_list(&contents);
+        if !list.is_empty() {
+            md.push_str("\n## Methods\n\n");
+            md.push_str(&list);
+        }
+    }
+    Ok(md)
+}
+
+/// Rewrites a relative href of the page, keeping external urls
+fn link(location: &Location, doc_dir: Option<&Path>, links: Links, href: &str) -> Option<String> {
+    if href.is_empty() || href == "#" {
+        return None;
+    }
+    if href.contains("://") {
+        return Some(href.into());
+    }
+    let url = location.url.join(href).ok().map(String::from);
+    if links == Links::Url {
+        return url;
+    }
+    let (page, id) = match href.split_once('#') {
+        Some((page, id)) => (page, Some(id)),
+        None => (href, None)
+    };
+    let file = if page.is_empty() {
+        location.file.clone()
+    } else {
+        location::normalize(&location.file.parent()?.join(page))
+    };
+    doc_dir
+        .and_then(|d| location::item_from_page(d, &file, id))
+        .map(|(path, _)| path)
+        .or(url)
+}
+
+/// Sections after the methods of the item, where impls of traits repeat their methods
+const TRAIT_IMPLS: &[&str] = &[
+    "trait-implementations",
+    "synthetic-implementations",
+    "blanket-implementations",
+    "implementors",
+    "foreign-impls"
+];
+
+/// Signatures of the methods of the item on the page, leaving out those of trait impls
+fn method_list(contents: &str) -> String {
+    let end = TRAIT_IMPLS
+        .iter()
+        .filter_map(|id| contents.find(&format!(r#"id="{}"#, id)))
+        .min()
+        .unwrap_or(contents.len());
+    let mut list = String::new();
+    for prefix in [r#"id="method."#, r#"id="tymethod."#] {
+        for (pos, _) in contents[..end].match_indices(prefix) {
+            let id_start = pos + 4;
+            let id = match contents[id_start..].find('"') {
+                Some(len) => &contents[id_start..id_start + len],
+                None => continue
+            };
+            let header = match html::element_by_id(contents, id) {
+                Some(h) => h,
+                None => continue
+            };
+            let code = header
+                .find("code-header")
+                .and_then(|i| header[..i].rfind('<'))
+                .and_then(|i| html::element_at(header, i))
+                .unwrap_or(header);
+            list.push_str(&format!("- `{}`\n", html::text(code).trim()));
+        }
+    }
+    list
+}
+
+/// Converts HTML into Markdown, rewriting hrefs with `link`
+pub fn render(fragment: &str, link: impl Fn(&str) -> Option<String>) -> String {
+    let mut m = Markdown::default();
+    for token in html::content_tokens(fragment) {
+        match token {
+            Token::Start { name, attrs, .. } => {
+                let class = html::attr(attrs, "class").unwrap_or_default();
+                let href = html::attr(attrs, "href").and_then(|h| link(&html::unescape(h)));
+                m.start(name, class, href);
+            }
+            Token::End(name) => m.end(name),
+            Token::Text(text) => m.text(&html::unescape(text))
+        }
+    }
+    m.finish()
+}
+
+#[derive(Debug, Default)]
+struct Markdown {
+    out: String,
+    /// Inline text of the current block
+    block: String,
+    /// The language of the open code block, and the tag that opened it
+    code: Option<String>,
+    code_tag: String,
+    code_depth: usize,
+    inline_code: usize,
+    lists: Vec<Option<usize>>,
+    /// Where link texts begin in `block` and their targets
+    links: Vec<(usize, Option<String>)>,
+    heading: bool,
+    /// Where the text of the current line begins in `block`, after a bullet or `#`
+    line_start: usize
+}
+
+impl Markdown {
+    fn start(&mut self, name: &str, class: &str, href: Option<String>) {
+        if self.code.is_some() {
+            if name == self.code_tag {
+                self.code_depth += 1;
+            }
+            if name == "br" {
+                self.block.push('\n');
+            }
+            return;
+        }
+        let is_code_header = class.split(' ').any(|c| c == "code-header");
+        match name {
+            "pre" => self.open_code(
+                name,
+                class
+                    .split(' ')
+                    .find_map(|c| c.strip_prefix("language-"))
+                    .or_else(|| class.split(' ').any(|c| c == "rust").then_some("rust"))
+                    .unwrap_or("")
+            ),
+            _ if is_code_header => self.open_code(name, "rust"),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush();
+                let level = name[1..].parse().unwrap_or(1);
+                self.block = format!("{} ", "#".repeat(level));
+                self.line_start = self.block.len();
+                self.heading = true;
+            }
+            // Paragraphs in a list item stay on its line
+            "p" | "div" | "section" | "details" | "blockquote" | "table" | "tr" | "dl"
+                if self.lists.is_empty() =>
+            {
+                self.flush()
+            }
+            "ul" => {
+                self.flush();
+                self.lists.push(None);
+            }
+            "ol" => {
+                self.flush();
+                self.lists.push(Some(0));
+            }
+            "li" => {
+                self.flush_line();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", n)
+                    }
+                    _ => "- ".into()
+                };
+                self.block = format!("{}{}", indent, bullet);
+                self.line_start = self.block.len();
+            }
+            "br" => {
+                self.block.push_str("  \n");
+                self.line_start = self.block.len();
+            }
+            "code" => {
+                self.inline_code += 1;
+                self.block.push('`');
+            }
+            "strong" | "b" => self.block.push_str("**"),
+            "em" | "i" => self.block.push('*'),
+            "a" if !self.heading => self.links.push((self.block.len(), href)),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        if self.code.is_some() {
+            if name == self.code_tag {
+                if self.code_depth == 0 {
+                    self.close_code();
+                } else {
+                    self.code_depth -= 1;
+                }
+            }
+            return;
+        }
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush();
+                self.heading = false;
+            }
+            "p" | "div" | "section" | "details" | "blockquote" | "table" | "tr" | "dl"
+                if self.lists.is_empty() =>
+            {
+                self.flush()
+            }
+            "ul" | "ol" => {
+                self.flush_line();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.flush();
+                }
+            }
+            "li" => self.flush_line(),
+            "code" => {
+                self.inline_code = self.inline_code.saturating_sub(1);
+                self.block.push('`');
+            }
+            "strong" | "b" => self.block.push_str("**"),
+            "em" | "i" => self.block.push('*'),
+            "a" if !self.heading => {
+                if let Some((start, href)) = self.links.pop() {
+                    if let Some(href) = href.filter(|_| start < self.block.len()) {
+                        self.block.insert(start, '[');
+                        self.block.push_str(&format!("]({})", href));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.code.is_some() {
+            self.block.push_str(text);
+            return;
+        }
+        for (i, word) in text.split(char::is_whitespace).enumerate() {
+            if i > 0 && !self.block.ends_with([' ', '\n']) && !self.block.is_empty() {
+                self.block.push(' ');
+            }
+            if self.inline_code > 0 {
+                self.block.push_str(word);
+            } else {
+                self.escaped(word);
+            }
+        }
+    }
+
+    /// Pushes a word of text so that it does not read as Markdown
+    fn escaped(&mut self, word: &str) {
+        // Markers of blocks count only at the beginning of a line, as in `1. ` or `- `
+        let marker = if self.block.len() == self.line_start {
+            let digits = word.bytes().take_while(u8::is_ascii_digit).count();
+            match word[digits..].chars().next() {
+                Some('.' | ')') if digits > 0 => Some(digits),
+                Some('-' | '+' | '=') if digits == 0 => Some(0),
+                _ => None
+            }
+        } else {
+            None
+        };
+        for (i, c) in word.char_indices() {
+            if Some(i) == marker
+                || matches!(
+                    c,
+                    '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~' | '&'
+                )
+            {
+                self.block.push('\\');
+            }
+            self.block.push(c);
+        }
+    }
+
+    fn open_code(&mut self, tag: &str, lang: &str) {
+        self.flush();
+        self.code = Some(lang.into());
+        self.code_tag = tag.into();
+    }
+
+    fn close_code(&mut self) {
+        let lang = self.code.take().unwrap_or_default();
+        let body = std::mem::take(&mut self.block);
+        self.out.push_str(&format!(
+            "```{}\n{}\n```\n\n",
+            lang,
+            body.trim_matches('\n')
+        ));
+    }
+
+    /// Ends the current line of a list item
+    fn flush_line(&mut self) {
+        self.line_start = 0;
+        let line = std::mem::take(&mut self.block);
+        if !line.trim().is_empty() {
+            self.out.push_str(line.trim_end());
+            self.out.push('\n');
+        }
+    }
+
+    /// Ends the current block with a blank line
+    fn flush(&mut self) {
+        self.line_start = 0;
+        let block = std::mem::take(&mut self.block);
+        if !block.trim().is_empty() {
+            self.out.push_str(block.trim());
+            self.out.push_str("\n\n");
+        } else if self.out.ends_with('\n') && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        let len = self.out.trim_end().len();
+        self.out.truncate(len);
+        self.out.push('\n');
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use url::Url;
+
+    #[test]
+    fn markdown_of_widget() {
+        let tree = crate::fixture::doc_tree();
+        let doc_dir = tree.doc_dir();
+        let page = doc_dir.join("deep/outer/inner/struct.Widget.html");
+        let location = Location::new(page.clone(), None).unwrap();
+        assert_eq!(
+            markdown(&location, Links::Path, true).unwrap(),
+            "# Struct Widget\n\
+             \n\
+             ```rust\n\
+             pub struct Widget\n\
+             ```\n\
+             \n\
+             A widget that is drawn on the screen.\n\
+             \n\
+             ```rust\n\
+             let w = Widget::new();\n\
+             ```\n\
+             \n\
+             ## Methods\n\
+             \n\
+             - `pub fn new() -> Widget`\n"
+        );
+        let location = Location::new(page, Some("method.new".into())).unwrap();
+        assert_eq!(
+            markdown(&location, Links::Path, false).unwrap(),
+            "```rust\npub fn new() -> Widget\n```\n\nCreates a widget like [`Choice::A`](deep::Choice::A)\n"
+        );
+        let url = Url::from_file_path(doc_dir.join("deep/enum.Choice.html")).unwrap();
+        assert_eq!(
+            markdown(&location, Links::Url, false).unwrap(),
+            format!(
+                "```rust\npub fn new() -> Widget\n```\n\nCreates a widget like [`Choice::A`]({}#variant.A)\n",
+                url
+            )
+        );
+        assert_eq!(
+            render(
+                "<p>a * b <em>c</em></p><ul><li>x</li><li><p>y</p></li></ul>",
+                |_| None
+            ),
+            "a \\* b *c*\n\n- x\n- y\n"
+        );
+        assert_eq!(
+            render(
+                "<p>1. snake_case &lt;T&gt; #1</p><ul><li>- x</li></ul><p>a<br>+ b 2.</p>",
+                |_| None
+            ),
+            "1\\. snake\\_case \\<T\\> \\#1\n\n- \\- x\n\na  \n\\+ b 2.\n"
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Opens urls in a browser, shared by the command line and editor integrations
+use crate::Error;
+use std::{
+    env,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::Command
+};
+
+/// A command line that takes a url
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opener {
+    program: String,
+    args: Vec<String>
+}
+
+impl Opener {
+    /// Chooses the configured command, then `$BROWSER`, then the opener of the platform
+    pub fn find(configured: Option<&str>) -> Result<Self, Error> {
+        let browser = env::var("BROWSER").ok();
+        let path = env::var_os("PATH");
+        Self::choose(configured, browser.as_deref(), |p| {
+            is_executable(p, path.as_deref())
+        })
+        .ok_or(Error::OpenerNotFound)
+    }
+
+    /// What [`Opener::find`] would run, or the opener of the platform when nothing is found,
+    /// for showing the command without running it
+    pub fn planned(configured: Option<&str>) -> Self {
+        Self::find(configured).unwrap_or_else(|_| Self::platform())
+    }
+
+    fn platform() -> Self {
+        let cmd = if cfg!(windows) {
+            "cmd /C start \"\""
+        } else if cfg!(target_os = "macos") {
+            "open"
+        } else {
+            "xdg-open"
+        };
+        Self::parse(cmd).expect("the command is not empty")
+    }
+
+    fn choose(
+        configured: Option<&str>,
+        browser: Option<&str>,
+        exists: impl Fn(&str) -> bool
+    ) -> Option<Self> {
+        if let Some(cmd) = configured {
+            return Self::parse(cmd);
+        }
+        // $BROWSER may list fallbacks separated by colons
+        let from_env = browser
+            .into_iter()
+            .flat_map(|b| b.split(':'))
+            .filter_map(Self::parse)
+            .find(|o| exists(&o.program));
+        from_env.or_else(|| Some(Self::platform()).filter(|o| exists(&o.program)))
+    }
+
+    fn parse(cmd: &str) -> Option<Self> {
+        let mut words = words(cmd).into_iter();
+        Some(Self {
+            program: words.next()?,
+            args: words.collect()
+        })
+    }
+
+    /// Substitutes `%s` with the url, or appends the url
+    pub fn command_line(&self, url: &str) -> Vec<String> {
+        let mut line = vec![self.program.clone()];
+        if self.args.iter().any(|a| a.contains("%s")) {
+            line.extend(self.args.iter().map(|a| a.replace("%s", url)));
+        } else {
+            line.extend(self.args.iter().cloned());
+            line.push(url.into());
+        }
+        line
+    }
+
+    /// Waits for the command so that terminal browsers can take over the tty
+    pub fn open(&self, url: &str) -> Result<(), Error> {
+        let line = self.command_line(url);
+        let status = Command::new(&line[0]).args(&line[1..]).status()?;
+        if !status.success() {
+            return Err(Error::OpenerFailed(line.join(" "), status));
+        }
+        Ok(())
+    }
+}
+
+/// Splits a command on whitespace, keeping double-quoted words
+pub(crate) fn words(cmd: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    let mut has_word = false;
+    for c in cmd.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                has_word = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if has_word {
+                    words.push(std::mem::take(&mut word));
+                    has_word = false;
+                }
+            }
+            c => {
+                word.push(c);
+                has_word = true;
+            }
+        }
+    }
+    if has_word {
+        words.push(word);
+    }
+    words
+}
+
+fn is_executable(program: &str, path: Option<&OsStr>) -> bool {
+    if Path::new(program).components().count() > 1 {
+        return Path::new(program).is_file();
+    }
+    let exts: &[&str] = if cfg!(windows) {
+        &["", ".exe", ".cmd", ".bat"]
+    } else {
+        &[""]
+    };
+    path.map(env::split_paths)
+        .into_iter()
+        .flatten()
+        .flat_map(|dir| {
+            exts.iter()
+                .map(move |e| dir.join(format!("{}{}", program, e)))
+        })
+        .any(|p: PathBuf| p.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choose_opener() {
+        let url = "file:///tmp/my%20docs/index.html";
+        let line = |o: Option<Opener>| o.map(|o| o.command_line(url));
+        assert_eq!(
+            line(Opener::choose(
+                Some(r#""my browser" --new-tab"#),
+                Some("w3m"),
+                |_| false
+            )),
+            Some(vec!["my browser".into(), "--new-tab".into(), url.into()])
+        );
+        assert_eq!(
+            line(Opener::choose(None, Some("lynx:w3m -o %s"), |p| p == "w3m")),
+            Some(vec!["w3m".into(), "-o".into(), url.into()])
+        );
+        assert_eq!(line(Opener::choose(None, Some("lynx"), |_| false)), None);
+        assert_eq!(Opener::parse("  "), None);
+        assert_eq!(
+            Opener::planned(Some("w3m")).command_line(url),
+            vec!["w3m".to_owned(), url.into()]
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! A short summary of an item for the preview window of fzf
+use crate::{
+    doc::Item,
+    html,
+    location::Resolver,
+    search_index, signature,
+    view::{self, Style},
+    which, Error
+};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime}
+};
+
+/// What a preview shows
+#[derive(Debug)]
+pub struct Preview {
+    pub item: Item,
+    pub file: PathBuf,
+    pub declaration: Option<String>,
+    /// The kind such as `deprecated` or `unstable`, and the text
+    pub badges: Vec<(String, String)>,
+    /// The first paragraph of the docs as HTML
+    pub summary: Option<String>
+}
+
+impl Preview {
+    pub fn new(resolver: &mut Resolver, line: &str) -> Result<Self, Error> {
+        let item = resolver.item(line)?;
+        let location = resolver.location(&item.line())?;
+        let contents = std::fs::read_to_string(&location.file)?;
+        let anchor = location.anchor.as_deref();
+        let fragment = view::docblock(&contents, anchor).unwrap_or_default();
+        Ok(Self {
+            declaration: signature::declaration(&contents, anchor),
+            badges: badges(&fragment),
+            summary: summary(&fragment).map(str::to_owned),
+            item,
+            file: location.file
+        })
+    }
+
+    pub fn render(&self, style: Style) -> String {
+        let paint = |sgr: &str, s: &str| {
+            if style.color {
+                format!("\x1b[{}m{}\x1b[0m", sgr, s)
+            } else {
+                s.to_owned()
+            }
+        };
+        let mut out = format!(
+            "{} {}\n",
+            paint("35", self.item.ty.as_str()),
+            paint("1", &self.item.full_path())
+        );
+        for (kind, text) in &self.badges {
+            out.push_str(&format!(
+                "{} {}\n",
+                paint("33", &format!("[{}]", kind)),
+                text
+            ));
+        }
+        if let Some(decl) = &self.declaration {
+            out.push('\n');
+            out.push_str(&paint("36", decl));
+            out.push('\n');
+        }
+        let desc = html::text(&self.item.desc);
+        if !desc.trim().is_empty() {
+            out.push('\n');
+            out.push_str(&view::render(&format!("<p>{}</p>", self.item.desc), style));
+        }
+        // The description is usually the first sentence of the summary
+        if let Some(summary) = self.summary.as_deref() {
+            let text = html::text(summary);
+            if signature::one_line(&text) != signature::one_line(&desc) {
+                out.push('\n');
+                out.push_str(&view::render(summary, style));
+            }
+        }
+        out
+    }
+}
+
+/// Stability, deprecation and portability notes
+fn badges(fragment: &str) -> Vec<(String, String)> {
+    fragment
+        .match_indices(r#"class="stab "#)
+        .filter_map(|(i, m)| {
+            let rest = &fragment[i + m.len()..];
+            let kind = &rest[..rest.find(['"', ' '])?];
+            let start = fragment[..i].rfind('<')?;
+            let text = signature::one_line(&html::text(html::element_at(fragment, start)?));
+            Some((kind.to_owned(), text))
+        })
+        .collect()
+}
+
+/// The first paragraph of the docs, not of the declaration
+fn summary(fragment: &str) -> Option<&str> {
+    let docs = fragment
+        .match_indices(r#"<div class="docblock""#)
+        .map(|(i, _)| &fragment[i..])
+        .next()?;
+    let start = docs.find("<p>")?;
+    html::element_at(docs, start)
+}
+
+/// Rendered previews on disk, valid while neither the page nor the search index is modified
+#[derive(Debug)]
+pub struct Cache {
+    dir: PathBuf
+}
+
+/// Entries older than this are removed when one is written
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// The most entries kept, removing the oldest
+const MAX_ENTRIES: usize = 2000;
+
+impl Cache {
+    /// Under `$XDG_CACHE_HOME` or `~/.cache`
+    pub fn new() -> Option<Self> {
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| Path::new(&h).join(".cache")))?;
+        Some(Self {
+            dir: base.join("rustdoc-index").join("preview")
+        })
+    }
+
+    /// Named by the FNV-1a hash of the key, which unlike `DefaultHasher` does not change
+    /// between builds
+    fn entry(&self, key: &str) -> PathBuf {
+        let hash = key.bytes().fold(0xcbf29ce484222325u64, |h, b| {
+            (h ^ u64::from(b)).wrapping_mul(0x100000001b3)
+        });
+        self.dir.join(format!("{:016x}", hash))
+    }
+
+    /// The text when the entry is of the key and the files it was made from are unchanged
+    pub fn get(&self, key: &str) -> Option<String> {
+        let entry = std::fs::read_to_string(self.entry(key)).ok()?;
+        let (header, text) = entry.split_once("\n\n")?;
+        let mut lines = header.lines();
+        if lines.next()? != key {
+            return None;
+        }
+        for line in lines {
+            let (modified, file) = line.split_once('\t')?;
+            if mtime(Path::new(file))? != modified {
+                return None;
+            }
+        }
+        Some(text.to_owned())
+    }
+
+    /// Fails silently as the cache is only for speed
+    pub fn put(&self, key: &str, page: &Path, text: &str) {
+        if key.contains('\n') {
+            return;
+        }
+        let index = which::doc_dir_of(page)
+            .ok()
+            .and_then(|d| search_index::ls_search_index(&d).ok().flatten());
+        let mut header = key.to_owned();
+        for file in std::iter::once(page).chain(index.as_deref()) {
+            match (file.to_str().filter(|f| !f.contains('\n')), mtime(file)) {
+                (Some(f), Some(m)) => header.push_str(&format!("\n{}\t{}", m, f)),
+                _ => return
+            }
+        }
+        if std::fs::create_dir_all(&self.dir).is_ok() {
+            self.evict();
+            let _ = std::fs::write(self.entry(key), format!("{}\n\n{}", header, text));
+        }
+    }
+
+    /// Removes entries older than [`MAX_AGE`], then the oldest beyond [`MAX_ENTRIES`]
+    fn evict(&self) {
+        let now = SystemTime::now();
+        let mut entries = match self.dir.read_dir() {
+            Ok(dir) => dir
+                .filter_map(|e| {
+                    let path = e.ok()?.path();
+                    Some((path.metadata().ok()?.modified().ok()?, path))
+                })
+                .collect::<Vec<_>>(),
+            Err(_) => return
+        };
+        entries.sort_by_key(|e| std::cmp::Reverse(e.0));
+        for (i, (modified, path)) in entries.iter().enumerate() {
+            let expired = now.duration_since(*modified).is_ok_and(|age| age > MAX_AGE);
+            // Leaves room for the entry being written
+            if expired || i + 1 >= MAX_ENTRIES {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+}
+
+fn mtime(file: &Path) -> Option<String> {
+    let modified = file.metadata().ok()?.modified().ok()?;
+    let since = modified.duration_since(SystemTime::UNIX_EPOCH).ok()?;
+    Some(format!("{}.{:09}", since.as_secs(), since.subsec_nanos()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preview_and_cache() {
+        let tree = crate::fixture::doc_tree();
+        let search_index = tree.search_index();
+        let mut resolver = Resolver::for_test(search_index.clone());
+        let plain = Style {
+            width: 40,
+            color: false
+        };
+        let preview = Preview::new(&mut resolver, "deep::outer::inner::Shape::area\ttymethod")
+            .unwrap()
+            .render(plain);
+        assert_eq!(
+            preview,
+            "tymethod deep::outer::inner::Shape::area\n\
+             [deprecated] Deprecated since 0.2.0: use perimeter\n\
+             \n\
+             fn area(&self) -> f64\n\
+             \n\
+             Computes the area\n"
+        );
+        let preview = Preview::new(&mut resolver, "deep::outer::inner::Widget")
+            .unwrap()
+            .render(plain);
+        assert_eq!(
+            preview,
+            "struct deep::outer::inner::Widget\n\
+             \n\
+             pub struct Widget\n\
+             \n\
+             A widget\n\
+             \n\
+             A widget that is drawn on the screen.\n"
+        );
+        let cache = Cache {
+            dir: search_index.parent().unwrap().join("cache")
+        };
+        let page = search_index.parent().unwrap().join("deep/enum.Choice.html");
+        let key = "deep::Choice\tenum\t40";
+        assert_eq!(cache.get(key), None);
+        cache.put(key, &page, "enum deep::Choice\n");
+        assert_eq!(cache.get(key).as_deref(), Some("enum deep::Choice\n"));
+        assert_eq!(cache.get("deep::Choice\tenum\t80"), None);
+        assert_eq!(cache.entry(key), cache.dir.join("04be28c0624f8f51"));
+        // Rebuilt docs have a new search index
+        let index = std::fs::File::options()
+            .append(true)
+            .open(&search_index)
+            .unwrap();
+        index
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(cache.get(key), None);
+    }
+}
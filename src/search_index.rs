@@ -1,5 +1,7 @@
 use crate::Error;
 use std::{
+    fs::File,
+    io::{BufRead, BufReader},
     path::{Path, PathBuf},
     process::Command
 };
@@ -14,49 +16,235 @@ pub fn ls_search_index(dir: &Path) -> Result<Option<PathBuf>, Error> {
     Ok(search_index)
 }
 
+/// Selects the cargo project like `--manifest-path` and `--package` of cargo subcommands
+#[derive(Debug, Default, Clone)]
+pub struct Manifest {
+    pub current_dir: Option<PathBuf>,
+    pub manifest_path: Option<PathBuf>,
+    pub package: Option<String>
+}
+
+/// Which documentation a search index belongs to
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RootKind {
+    Std,
+    Local
+}
+
+impl RootKind {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            RootKind::Std => "std",
+            RootKind::Local => "local"
+        }
+    }
+}
+
 pub fn find_std() -> Result<Option<PathBuf>, Error> {
-    let output = Command::new("rustup").args(&["doc", "--path"]).output()?;
+    match find_std_doc_dir()? {
+        Some(dir) => ls_search_index(&dir),
+        None => Ok(None)
+    }
+}
+
+pub fn find_std_doc_dir() -> Result<Option<PathBuf>, Error> {
+    let output = Command::new("rustup").args(["doc", "--path"]).output()?;
     let out = unsafe { String::from_utf8_unchecked(output.stdout) };
     let file = PathBuf::from(out);
-    let dir = match file.parent() {
-        Some(dir) => dir,
-        None => return Ok(None)
-    };
-    ls_search_index(dir)
+    Ok(file.parent().filter(|d| d.is_dir()).map(PathBuf::from))
 }
 
-pub fn find_local(current_dir: Option<PathBuf>) -> Result<Option<PathBuf>, Error> {
-    let meta = match metadata(current_dir) {
+/// `library` of the rust-src component, which has a directory per std crate
+pub fn find_rust_src() -> Result<Option<PathBuf>, Error> {
+    let output = Command::new("rustc")
+        .args(["--print", "sysroot"])
+        .output()?;
+    let sysroot = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    let dir = sysroot.join("lib/rustlib/src/rust/library");
+    Ok(dir.is_dir().then_some(dir))
+}
+
+/// The channel of doc.rust-lang.org for the toolchain, such as `nightly` or `1.60.0`
+pub fn rustc_channel() -> Result<Option<String>, Error> {
+    let output = Command::new("rustc").arg("--version").output()?;
+    Ok(String::from_utf8(output.stdout)
+        .ok()
+        .and_then(|out| channel(&out)))
+}
+
+fn channel(version: &str) -> Option<String> {
+    // rustc 1.60.0-nightly (7737e0b5c 2022-04-04)
+    let version = version.split_whitespace().nth(1)?;
+    Some(match version.split_once('-') {
+        Some((_, pre)) if pre.starts_with("beta") => "beta".into(),
+        Some(_) => "nightly".into(),
+        None => version.into()
+    })
+}
+
+pub fn find_local(manifest: &Manifest) -> Result<Option<PathBuf>, Error> {
+    match find_local_doc_dir(manifest)? {
+        Some(dir) => ls_search_index(&dir),
+        None => Ok(None)
+    }
+}
+
+pub fn find_local_doc_dir(manifest: &Manifest) -> Result<Option<PathBuf>, Error> {
+    let meta = match metadata(manifest, true) {
         Ok(x) => x,
         Err(_) => return Ok(None)
     };
     let dir = meta.target_directory.join_os("doc");
-    if !dir.is_dir() {
-        return Ok(None);
+    Ok(dir.is_dir().then_some(dir))
+}
+
+/// Doc dirs of std and the project, including ones whose search index is not supported
+pub async fn doc_roots(manifest: Manifest) -> Result<Vec<(RootKind, PathBuf)>, Error> {
+    let async_find_std = tokio::spawn(async { find_std_doc_dir() });
+    let async_find_local = tokio::spawn(async move { find_local_doc_dir(&manifest) });
+    let (std, local) = tokio::join!(async_find_std, async_find_local);
+    let mut res = Vec::with_capacity(2);
+    if let Some(std) = std?? {
+        res.push((RootKind::Std, std));
+    }
+    if let Some(local) = local?? {
+        res.push((RootKind::Local, local));
     }
-    ls_search_index(&dir)
+    Ok(res)
 }
 
-pub async fn search_indexes(current_dir: Option<PathBuf>) -> Result<Vec<PathBuf>, Error> {
+/// `window.ALL_CRATES = ["std","core"];` of the doc dir
+///
+/// Rust-docs names it `crates1.60.0.js` with the resource suffix.
+pub fn read_crates_js(doc_dir: &Path) -> Result<Option<Vec<String>>, Error> {
+    let file = doc_dir.read_dir()?.find_map(|e| -> Option<_> {
+        let e = e.ok()?;
+        let name = e.file_name().into_string().ok()?;
+        (name.starts_with("crates") && name.ends_with(".js")).then(|| e.path())
+    });
+    let file = match file {
+        Some(file) => file,
+        None => return Ok(None)
+    };
+    let js = std::fs::read_to_string(&file)?;
+    let invalid = || Error::InvalidFormat(file.display().to_string());
+    let list = js
+        .find("ALL_CRATES")
+        .and_then(|i| js[i..].find('=').map(|j| &js[i + j + 1..]))
+        .ok_or_else(invalid)?;
+    let names = serde_json::Deserializer::from_str(list.trim_start())
+        .into_iter::<Vec<String>>()
+        .next()
+        .ok_or_else(invalid)?
+        .map_err(|e| Error::SerdeJson(file.display().to_string(), e))?;
+    Ok(Some(names))
+}
+
+/// Names of crates in the search index, from `crates.js` or else the keys of its lines
+pub fn crate_names(search_index: &Path) -> Result<Vec<String>, Error> {
+    if let Some(names) = search_index
+        .parent()
+        .map(read_crates_js)
+        .transpose()?
+        .flatten()
+    {
+        return Ok(names);
+    }
+    let reader = BufReader::new(File::open(search_index)?);
+    let mut names = Vec::new();
+    for line in reader.lines().skip(1) {
+        names.extend(crate::crate_of_line(&line?).map(str::to_owned));
+    }
+    Ok(names)
+}
+
+pub async fn search_indexes(manifest: Manifest) -> Result<Vec<PathBuf>, Error> {
+    let roots = search_index_roots(manifest).await?;
+    Ok(roots.into_iter().map(|(_, p)| p).collect())
+}
+
+pub async fn search_index_roots(manifest: Manifest) -> Result<Vec<(RootKind, PathBuf)>, Error> {
     let async_find_std = tokio::spawn(async { find_std() });
-    let async_find_local = tokio::spawn(async { find_local(current_dir) });
+    let async_find_local = tokio::spawn(async move { find_local(&manifest) });
     let (std, local) = tokio::join!(async_find_std, async_find_local);
     let mut res = Vec::with_capacity(2);
     if let Some(std) = std?? {
-        res.push(std);
+        res.push((RootKind::Std, std));
     }
     if let Some(local) = local?? {
-        res.push(local);
+        res.push((RootKind::Local, local));
     }
     Ok(res)
 }
 
-fn metadata(current_dir: Option<PathBuf>) -> Result<cargo_metadata::Metadata, Error> {
+/// `no_deps` skips resolving dependencies, which is enough to find `target_directory`
+pub fn metadata(manifest: &Manifest, no_deps: bool) -> Result<cargo_metadata::Metadata, Error> {
     let mut cmd = cargo_metadata::MetadataCommand::new();
-    if let Some(d) = current_dir {
+    if let Some(d) = &manifest.current_dir {
         cmd.current_dir(d);
     }
-    cmd.no_deps();
+    if let Some(p) = &manifest.manifest_path {
+        cmd.manifest_path(p);
+    }
+    if no_deps {
+        cmd.no_deps();
+    }
     cmd.other_options(vec![String::from("--offline")]);
     Ok(cmd.exec()?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_channel() {
+        assert_eq!(
+            channel("rustc 1.60.0 (7737e0b5c 2022-04-04)"),
+            Some("1.60.0".into())
+        );
+        assert_eq!(
+            channel("rustc 1.61.0-beta.3 (2431a974c 2022-04-17)"),
+            Some("beta".into())
+        );
+        assert_eq!(
+            channel("rustc 1.62.0-nightly (311e2683e 2022-04-18)"),
+            Some("nightly".into())
+        );
+        assert_eq!(channel(""), None);
+    }
+
+    #[test]
+    fn crates_of_doc_dir() {
+        use crate::fixture::DocTree;
+        use rayon::prelude::*;
+        let names = vec!["deep".to_owned(), "other".to_owned()];
+        // rust-docs adds the resource suffix as to the search index
+        let suffixed = DocTree::new(&[(
+            "crates1.60.0.js",
+            r#"window.ALL_CRATES = ["deep","other"];"#
+        )]);
+        assert_eq!(
+            read_crates_js(suffixed.doc_dir()).unwrap(),
+            Some(names.clone())
+        );
+        let newer = DocTree::new(&[(
+            "crates.js",
+            "window.ALL_CRATES = [\"deep\"];\n//{\"start\":21,\"fragment_lengths\":[6]}\n"
+        )]);
+        assert_eq!(
+            crate_names(&newer.search_index()).unwrap(),
+            vec!["deep".to_owned()]
+        );
+        let without = crate::fixture::doc_tree();
+        let search_index = without.search_index();
+        assert_eq!(read_crates_js(without.doc_dir()).unwrap(), None);
+        assert_eq!(crate_names(&search_index).unwrap(), names);
+        let krates = crate::read_search_index_of(&search_index, |name| name == "other")
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(krates.len(), 1);
+        assert_eq!(krates[0].0, "other");
+    }
+}
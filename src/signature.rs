@@ -0,0 +1,130 @@
+//! Declarations of items as rustdoc renders them
+use crate::{
+    doc::Item,
+    html::{self, Token},
+    location::{self, Location, LocationError},
+    Error
+};
+use std::path::{Path, PathBuf};
+
+/// The declaration of the item at the location
+pub fn signature(location: &Location) -> Result<String, Error> {
+    let contents = std::fs::read_to_string(&location.file)?;
+    declaration(&contents, location.anchor.as_deref())
+        .ok_or_else(|| LocationError::DeclarationNotFound.into())
+}
+
+/// The `item-decl` block of the page, or the `code-header` of the anchor
+pub fn declaration(contents: &str, anchor: Option<&str>) -> Option<String> {
+    let scope = match anchor {
+        Some(anchor) => html::element_by_id(contents, anchor)?,
+        None => contents
+    };
+    let class = if anchor.is_some() {
+        "code-header"
+    } else {
+        "item-decl"
+    };
+    let start = scope
+        .match_indices(class)
+        .filter_map(|(i, _)| scope[..i].rfind('<'))
+        .find(|&i| !scope[i..].starts_with("</"))?;
+    let element = html::element_at(scope, start)?;
+    let text = code_text(element);
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_owned())
+}
+
+/// Text of code, with `<br>` and indents of where clauses
+fn code_text(fragment: &str) -> String {
+    let mut text = String::new();
+    for token in html::content_tokens(fragment) {
+        match token {
+            Token::Start { name: "br", .. } => text.push('\n'),
+            Token::Text(s) => text.push_str(&html::unescape(s).replace('\u{a0}', " ")),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Joins a multi-line declaration into one line for a column
+pub fn one_line(declaration: &str) -> String {
+    declaration.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Declarations of many items, keeping only the last page read since items of a page mostly
+/// come together in the index
+#[derive(Debug, Default)]
+pub struct Signatures {
+    last: Option<(PathBuf, Option<String>)>
+}
+
+impl Signatures {
+    pub fn get(&mut self, file: &Path, anchor: Option<&str>) -> Option<String> {
+        if self.last.as_ref().map(|(f, _)| f.as_path()) != Some(file) {
+            self.last = Some((file.to_owned(), std::fs::read_to_string(file).ok()));
+        }
+        let contents = self.last.as_ref()?.1.as_deref()?;
+        declaration(contents, anchor)
+    }
+
+    /// Follows re-exports and redirects from the page computed from the search index
+    pub fn of_item(&mut self, doc_dir: &Path, item: &Item) -> Option<String> {
+        let (file, id) = location::item_file(item);
+        let (file, id) = location::follow(doc_dir.join(file), id);
+        self.get(&file, id.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declarations() {
+        let tree = crate::fixture::doc_tree();
+        let search_index = tree.search_index();
+        let doc_dir = tree.doc_dir();
+        let widget = doc_dir.join("deep/outer/inner/struct.Widget.html");
+        let location = |id: Option<&str>| Location::new(widget.clone(), id.map(str::to_owned));
+        assert_eq!(
+            signature(&location(None).unwrap()).unwrap(),
+            "pub struct Widget"
+        );
+        assert_eq!(
+            signature(&location(Some("method.new")).unwrap()).unwrap(),
+            "pub fn new() -> Widget"
+        );
+        assert_eq!(
+            signature(&location(Some("method.fmt-1")).unwrap()).unwrap(),
+            "fn fmt(&self, f: &mut Formatter<'_>) -> Result"
+        );
+        let module = Location::new(doc_dir.join("deep/outer/index.html"), None).unwrap();
+        assert!(signature(&module).is_err());
+        assert_eq!(
+            declaration(
+                r#"<section id="method.get"><h4 class="code-header">pub fn get&lt;Q&gt;(&amp;self) <span class="where fmt-newline">where<br>&nbsp;&nbsp;&nbsp;&nbsp;Q: Eq,</span></h4></section>"#,
+                Some("method.get")
+            )
+            .as_deref(),
+            Some("pub fn get<Q>(&self) where\n    Q: Eq,")
+        );
+        let krate = crate::read_crate(&search_index, "deep").unwrap().unwrap();
+        let mut signatures = Signatures::default();
+        let decls = krate
+            .entries()
+            .iter()
+            .map(|item| (item.line(), signatures.of_item(doc_dir, item)))
+            .filter_map(|(line, decl)| Some((line, decl?)))
+            .collect::<Vec<_>>();
+        assert!(decls.contains(&(
+            "deep::outer::inner::Widget::new\tmethod".into(),
+            "pub fn new() -> Widget".into()
+        )));
+        assert!(decls.contains(&(
+            "deep::outer::inner::Shape::area\ttymethod".into(),
+            "fn area(&self) -> f64".into()
+        )));
+    }
+}
@@ -0,0 +1,178 @@
+//! Source code of items through the `src` links of their pages
+use crate::{
+    html::{self, Token},
+    location::{self, Location, LocationError},
+    which, Error
+};
+use std::path::{Component, Path, PathBuf};
+
+/// A line range of a source page of docs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    /// `<doc dir>/src/<crate>/<file>.rs.html`
+    pub page: PathBuf,
+    pub krate: String,
+    /// The path of the file relative to the directory of the crate root
+    pub file: PathBuf,
+    pub first: usize,
+    pub last: usize
+}
+
+impl Source {
+    /// Follows the `src` link of the item at the location
+    pub fn new(location: &Location) -> Result<Self, Error> {
+        let contents = std::fs::read_to_string(&location.file)?;
+        let href = source_link(&contents, location.anchor.as_deref())
+            .ok_or(LocationError::SourceNotFound)?;
+        let (page, lines) = href.split_once('#').unwrap_or((&href, ""));
+        let page = location::normalize(
+            &location
+                .file
+                .parent()
+                .ok_or(LocationError::FileNotFound)?
+                .join(page)
+        );
+        let (first, last) = line_range(lines).ok_or(LocationError::InvalidFormat)?;
+        let doc_dir = which::doc_dir_of(&page)?;
+        let mut rel = page
+            .strip_prefix(doc_dir.join("src"))
+            .map_err(|_| LocationError::SourceNotFound)?
+            .components();
+        let krate = rel
+            .next()
+            .ok_or(LocationError::SourceNotFound)?
+            .as_os_str()
+            .to_string_lossy()
+            .into_owned();
+        // Files outside of the crate root directory are under `up`
+        let file = rel
+            .map(|c| match c.as_os_str().to_str() {
+                Some("up") => Component::ParentDir.as_os_str(),
+                _ => c.as_os_str()
+            })
+            .collect::<PathBuf>()
+            .with_extension("");
+        Ok(Self {
+            page,
+            krate,
+            file,
+            first,
+            last
+        })
+    }
+
+    /// The original file under the directory of the crate root, if it exists
+    pub fn original(&self, src_dir: &Path) -> Option<PathBuf> {
+        let file = location::normalize(&src_dir.join(&self.file));
+        file.is_file().then_some(file)
+    }
+
+    /// The lines of the original file, or of the source page without it
+    pub fn text(&self, original: Option<&Path>) -> Result<String, Error> {
+        let code = match original {
+            Some(file) => std::fs::read_to_string(file)?,
+            None => {
+                let contents = std::fs::read_to_string(&self.page)?;
+                code_of_page(&contents).ok_or(LocationError::SourceNotFound)?
+            }
+        };
+        let lines = code
+            .lines()
+            .skip(self.first - 1)
+            .take(self.last + 1 - self.first)
+            .collect::<Vec<_>>();
+        if lines.is_empty() {
+            return Err(LocationError::SourceNotFound.into());
+        }
+        Ok(lines.join("\n") + "\n")
+    }
+}
+
+/// The href of the `source` link in the header of the anchor, or of the page
+pub fn source_link(contents: &str, anchor: Option<&str>) -> Option<String> {
+    let scope = match anchor {
+        Some(anchor) => html::element_by_id(contents, anchor)?,
+        // The sidebar may come before h1, and implementations come after the first h2
+        None => {
+            let h1 = contents.find("<h1").unwrap_or(0);
+            let rest = &contents[h1..];
+            &rest[..rest.find("<h2").unwrap_or(rest.len())]
+        }
+    };
+    html::tokens(scope).find_map(|token| match token {
+        Token::Start {
+            name: "a", attrs, ..
+        } => {
+            let class = html::attr(attrs, "class").unwrap_or_default();
+            let href = html::attr(attrs, "href")?;
+            (class.split(' ').any(|c| c == "src" || c == "srclink") && href != "#")
+                .then(|| html::unescape(href))
+        }
+        _ => None
+    })
+}
+
+/// `10-42`, `L10-L42` or `10`
+fn line_range(fragment: &str) -> Option<(usize, usize)> {
+    let line = |s: &str| {
+        s.trim_start_matches('L')
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| n > 0)
+    };
+    match fragment.split_once('-') {
+        Some((first, last)) => Some((line(first)?, line(last)?)).filter(|(f, l)| f <= l),
+        None => line(fragment).map(|n| (n, n))
+    }
+}
+
+/// The code of a source page without line numbers
+fn code_of_page(contents: &str) -> Option<String> {
+    let start = contents.match_indices("<pre ").map(|(i, _)| i).find(|&i| {
+        let tag = &contents[i..i + contents[i..].find('>').unwrap_or(0)];
+        html::attr(tag, "class").is_some_and(|c| c.split(' ').any(|c| c == "rust"))
+    })?;
+    Some(html::text(html::element_at(contents, start)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::DocTree;
+
+    #[test]
+    fn source_of_items() {
+        let tree = crate::fixture::doc_tree();
+        let doc_dir = tree.doc_dir();
+        let widget = doc_dir.join("deep/outer/inner/struct.Widget.html");
+        let new = Location::new(widget.clone(), Some("method.new".into())).unwrap();
+        let source = Source::new(&new).unwrap();
+        assert_eq!(
+            source,
+            Source {
+                page: doc_dir.join("src/deep/lib.rs.html"),
+                krate: "deep".into(),
+                file: "lib.rs".into(),
+                first: 3,
+                last: 6
+            }
+        );
+        assert_eq!(
+            source.text(None).unwrap(),
+            "    /// Creates a widget like [`Choice::A`]\n    pub fn new() -> Widget {\n        Widget\n    }\n"
+        );
+        assert_eq!(source.original(&doc_dir.join("crate-src")), None);
+        let with_src = DocTree::new(&[("crate-src/lib.rs", "a\nb\nc\nd\ne\nf\ng\n")]);
+        let original = source
+            .original(&with_src.doc_dir().join("crate-src"))
+            .unwrap();
+        assert_eq!(source.text(Some(&original)).unwrap(), "c\nd\ne\nf\n");
+        let top = Source::new(&Location::new(widget, None).unwrap()).unwrap();
+        assert_eq!(top.text(None).unwrap(), "pub struct Widget;\n");
+        let shape = doc_dir.join("deep/outer/inner/trait.Shape.html");
+        assert!(Source::new(&Location::new(shape, None).unwrap()).is_err());
+        assert_eq!(line_range("L10-L42"), Some((10, 42)));
+        assert_eq!(line_range("7"), Some((7, 7)));
+        assert_eq!(line_range("0"), None);
+    }
+}
@@ -0,0 +1,360 @@
+//! Renders docblocks of rustdoc pages as terminal text
+use crate::{
+    html::{self, Token},
+    location::{Location, LocationError},
+    Error
+};
+use std::{
+    env,
+    io::Write,
+    process::{Command, Stdio}
+};
+
+/// How to render text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Style {
+    pub width: usize,
+    pub color: bool
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            width: 80,
+            color: true
+        }
+    }
+}
+
+const MDBOOK: &str = "<!-- Book generated using mdBook -->";
+
+/// Renders the item of the page, or the section of the anchor
+pub fn view(location: &Location, style: Style) -> Result<String, Error> {
+    let contents = std::fs::read_to_string(&location.file)?;
+    let fragment =
+        docblock(&contents, location.anchor.as_deref()).ok_or(LocationError::ItemNotFound)?;
+    Ok(render(&fragment, style))
+}
+
+/// The title, declaration, badges and top docs, or the header of the anchor and the rest
+pub fn docblock(contents: &str, anchor: Option<&str>) -> Option<String> {
+    // Sections of mdBook pages and of the single error index are not elements
+    if contents.contains(MDBOOK) || anchor.is_some_and(crate::errors::is_code) {
+        return match anchor {
+            Some(anchor) => html::section(contents, anchor),
+            None => html::element_at(contents, contents.find("<main")?)
+        }
+        .map(str::to_owned);
+    }
+    let anchor = match anchor {
+        Some(anchor) => anchor,
+        None => return Some(top_docblock(contents))
+    };
+    let header = html::element_by_id(contents, anchor)?;
+    let end = header.as_ptr() as usize - contents.as_ptr() as usize + header.len();
+    let mut rest = contents[end..].trim_start();
+    // Since 1.58 the docs of a member follow `</summary>` of its toggle
+    while let Some(r) = rest.strip_prefix("</summary>") {
+        rest = r.trim_start();
+    }
+    let mut parts = vec![header];
+    for class in [r#"class="item-info""#, r#"class="docblock"#] {
+        let is_next = rest.starts_with('<') && rest[..rest.find('>').unwrap_or(0)].contains(class);
+        if let Some(e) = is_next.then(|| html::element_at(rest, 0)).flatten() {
+            parts.push(e);
+            rest = rest[e.len()..].trim_start();
+        }
+    }
+    Some(parts.concat())
+}
+
+fn top_docblock(contents: &str) -> String {
+    // Implementations and members come after the first h2
+    let top = &contents[..contents.find("<h2").unwrap_or(contents.len())];
+    let mut parts = Vec::new();
+    if let Some(h1) = top.find("<h1").and_then(|i| html::element_at(top, i)) {
+        parts.push(h1);
+    }
+    let decl = top
+        .find("item-decl")
+        .and_then(|i| top[..i].rfind('<'))
+        .and_then(|i| html::element_at(top, i));
+    parts.extend(decl);
+    let after_decl = decl.map_or(0, |d| d.as_ptr() as usize - top.as_ptr() as usize + d.len());
+    let rest = &top[after_decl..];
+    let docs_pos = rest.find(r#"<div class="docblock""#);
+    // Stability and deprecation come between the declaration and the docs
+    let info = rest
+        .find(r#"class="item-info""#)
+        .filter(|&i| docs_pos.is_none_or(|d| i < d))
+        .and_then(|i| rest[..i].rfind('<'))
+        .and_then(|i| html::element_at(rest, i));
+    parts.extend(info);
+    parts.extend(docs_pos.and_then(|i| html::element_at(rest, i)));
+    parts.concat()
+}
+
+/// Renders HTML into wrapped text with ANSI colors
+pub fn render(fragment: &str, style: Style) -> String {
+    let mut r = Renderer {
+        style,
+        ..Default::default()
+    };
+    for token in html::content_tokens(fragment) {
+        match token {
+            Token::Start { name, attrs, .. } => {
+                r.start(name, html::attr(attrs, "class").unwrap_or_default())
+            }
+            Token::End(name) => r.end(name),
+            Token::Text(text) => r.text(&html::unescape(text))
+        }
+    }
+    r.finish()
+}
+
+#[derive(Debug, Default)]
+struct Renderer {
+    style: Style,
+    out: String,
+    line: String,
+    line_width: usize,
+    pending_space: bool,
+    /// Open elements, the SGR parameter each of them adds and whether it is a docblock
+    open: Vec<(String, Option<&'static str>, bool)>,
+    pre: usize,
+    lists: Vec<Option<usize>>
+}
+
+impl Renderer {
+    fn indent(&self) -> usize {
+        // Code examples in docs are indented but declarations are not
+        let in_docs = self.open.iter().any(|(_, _, docs)| *docs);
+        self.lists.len() * 2 + if self.pre > 0 && in_docs { 4 } else { 0 }
+    }
+
+    fn start(&mut self, name: &str, class: &str) {
+        match name {
+            "p" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "pre" | "ul" | "ol" | "table"
+            | "blockquote" | "dl" => self.blank_line(),
+            "div" | "section" | "details" | "tr" | "dt" | "dd" => self.newline(),
+            "br" => self.newline(),
+            "li" => {
+                self.newline();
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", n)
+                    }
+                    _ => "• ".into()
+                };
+                self.line = " ".repeat(self.indent().saturating_sub(2));
+                self.line.push_str(&bullet);
+                self.line_width = self.indent().saturating_sub(2) + bullet.chars().count();
+            }
+            "td" | "th" => self.pending_space = true,
+            _ => {}
+        }
+        match name {
+            "pre" => self.pre += 1,
+            "ul" => self.lists.push(None),
+            "ol" => self.lists.push(Some(0)),
+            _ => {}
+        }
+        if html::is_void(name) {
+            return;
+        }
+        let sgr = match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "strong" | "b" | "th" => Some("1"),
+            "em" | "i" => Some("3"),
+            "code" if self.pre == 0 => Some("36"),
+            _ => class.split(' ').find_map(highlight)
+        };
+        let docs = class.split(' ').any(|c| c == "docblock") && !class.contains("item-decl");
+        self.open.push((name.into(), sgr, docs));
+    }
+
+    fn end(&mut self, name: &str) {
+        if let Some(i) = self.open.iter().rposition(|(n, _, _)| n == name) {
+            self.open.truncate(i);
+        }
+        match name {
+            "pre" => self.pre = self.pre.saturating_sub(1),
+            "ul" | "ol" => {
+                self.lists.pop();
+            }
+            _ => {}
+        }
+        match name {
+            "p" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "pre" | "ul" | "ol" | "table"
+            | "blockquote" | "dl" => self.blank_line(),
+            "div" | "section" | "details" | "tr" | "li" | "dt" | "dd" => self.newline(),
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.pre > 0 {
+            for (i, l) in text.split('\n').enumerate() {
+                if i > 0 {
+                    self.newline();
+                }
+                if self.line.is_empty() {
+                    self.line = " ".repeat(self.indent());
+                    self.line_width = self.indent();
+                }
+                self.push(l);
+            }
+            return;
+        }
+        if text.starts_with(char::is_whitespace) {
+            self.pending_space = true;
+        }
+        for word in text.split_whitespace() {
+            let width = word.chars().count();
+            let space = usize::from(self.pending_space && self.line_width > self.indent());
+            if self.line_width > self.indent() && self.line_width + space + width > self.style.width
+            {
+                self.newline();
+            } else if space == 1 {
+                self.line.push(' ');
+                self.line_width += 1;
+            }
+            if self.line.is_empty() {
+                self.line = " ".repeat(self.indent());
+                self.line_width = self.indent();
+            }
+            self.push(word);
+            self.pending_space = true;
+        }
+        self.pending_space = text.ends_with(char::is_whitespace);
+    }
+
+    fn push(&mut self, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        let sgr = self
+            .open
+            .iter()
+            .filter_map(|(_, sgr, _)| *sgr)
+            .collect::<Vec<_>>()
+            .join(";");
+        if self.style.color && !sgr.is_empty() {
+            self.line.push_str(&format!("\x1b[{}m{}\x1b[0m", sgr, s));
+        } else {
+            self.line.push_str(s);
+        }
+        self.line_width += s.chars().count();
+    }
+
+    fn newline(&mut self) {
+        if !self.line.trim().is_empty() {
+            self.out.push_str(self.line.trim_end());
+            self.out.push('\n');
+        }
+        self.line.clear();
+        self.line_width = 0;
+        self.pending_space = false;
+    }
+
+    fn blank_line(&mut self) {
+        self.newline();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.newline();
+        let len = self.out.trim_end().len();
+        self.out.truncate(len);
+        self.out.push('\n');
+        self.out
+    }
+}
+
+/// Colors of classes in code highlighted by rustdoc
+fn highlight(class: &str) -> Option<&'static str> {
+    Some(match class {
+        "kw" | "kw-2" => "35",
+        "string" => "32",
+        "comment" | "doccomment" => "90",
+        "number" | "bool-val" | "lifetime" => "33",
+        "macro" | "macro-nonterminal" => "34",
+        "attribute" | "attr" => "36",
+        "self" => "31",
+        "fn" | "fnname" | "method" | "tymethod" => "1",
+        _ => return None
+    })
+}
+
+/// Writes the text through `$PAGER`, or directly if stdout is not a terminal
+pub fn page(text: &str) -> Result<(), Error> {
+    use std::io::IsTerminal;
+    let pager = env::var("PAGER").unwrap_or_else(|_| "less".into());
+    let words = crate::open::words(&pager);
+    if !std::io::stdout().is_terminal() || words.is_empty() || pager == "cat" {
+        std::io::stdout().write_all(text.as_bytes())?;
+        return Ok(());
+    }
+    let mut command = Command::new(&words[0]);
+    command.args(&words[1..]).stdin(Stdio::piped());
+    // Like git, let less pass colors through and quit on a short page
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    let mut child = command.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager may quit before reading everything
+        match stdin.write_all(text.as_bytes()) {
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {}
+            r => r?
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_docblocks() {
+        let tree = crate::fixture::doc_tree();
+        let search_index = tree.search_index();
+        let page = search_index
+            .parent()
+            .unwrap()
+            .join("deep/outer/inner/struct.Widget.html");
+        let plain = Style {
+            width: 24,
+            color: false
+        };
+        let location = Location::new(page.clone(), None).unwrap();
+        assert_eq!(
+            view(&location, plain).unwrap(),
+            "Struct Widget\n\
+             \n\
+             pub struct Widget\n\
+             \n\
+             A widget that is drawn\n\
+             on the screen.\n\
+             \n\
+             \x20   let w = Widget::new();\n"
+        );
+        let location = Location::new(page, Some("method.new".into())).unwrap();
+        assert_eq!(
+            view(&location, plain).unwrap(),
+            "pub fn new() -> Widget\n\nCreates a widget like\nChoice::A\n"
+        );
+        let colored = render(
+            r#"<div class="docblock"><pre class="rust"><code><span class="kw">let</span> x</code></pre><p>A <code>&amp;str</code></p></div>"#,
+            Style::default()
+        );
+        assert_eq!(
+            colored,
+            "    \x1b[35mlet\x1b[0m x\n\nA \x1b[36m&str\x1b[0m\n"
+        );
+    }
+}
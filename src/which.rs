@@ -0,0 +1,172 @@
+use crate::{
+    doc::ItemType,
+    location::{self, LocationError},
+    search_index::{self, Manifest},
+    Error
+};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// An item found from a page of docs
+#[derive(Debug, Serialize)]
+pub struct Which {
+    pub path: String,
+    #[serde(serialize_with = "serialize_item_type")]
+    pub kind: ItemType,
+    pub file: PathBuf,
+    pub anchor: Option<String>
+}
+
+impl Which {
+    /// A line of list
+    pub fn line(&self) -> String { format!("{}\t{}", self.path, self.kind.as_str()) }
+}
+
+fn serialize_item_type<S: serde::Serializer>(ty: &ItemType, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(ty.as_str())
+}
+
+/// Finds the item of a `file://` url, a path of a local page, or a url of doc.rust-lang.org or
+/// docs.rs mapped onto installed docs
+pub fn which(target: &str, manifest: &Manifest) -> Result<Which, Error> {
+    let (doc_dir, file, anchor) = match Url::parse(target) {
+        Ok(url) if url.scheme() == "file" => {
+            let file = url
+                .to_file_path()
+                .map_err(|_| LocationError::InvalidFormat)?;
+            (doc_dir_of(&file)?, file, url.fragment().map(str::to_owned))
+        }
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
+            let (is_std, rel) = web_page(&url).ok_or(LocationError::InvalidFormat)?;
+            let search_index = if is_std {
+                search_index::find_std()?
+            } else {
+                search_index::find_local(manifest)?
+            }
+            .ok_or(LocationError::DocNotFound)?;
+            let doc_dir = search_index.parent().unwrap().to_owned();
+            let file = doc_dir.join(rel);
+            (doc_dir, file, url.fragment().map(str::to_owned))
+        }
+        _ => {
+            let (file, anchor) = match target.split_once('#') {
+                Some((file, anchor)) => (file, Some(anchor.to_owned())),
+                None => (target, None)
+            };
+            let file = Path::new(file).canonicalize()?;
+            (doc_dir_of(&file)?, file, anchor)
+        }
+    };
+    let (file, anchor) = location::follow(file, anchor);
+    if !file.is_file() {
+        return Err(LocationError::FileNotFound.into());
+    }
+    let (path, kind) = location::item_from_page(&doc_dir, &file, anchor.as_deref())
+        .ok_or(LocationError::ItemNotFound)?;
+    Ok(Which {
+        path,
+        kind,
+        file,
+        anchor
+    })
+}
+
+/// The nearest ancestor which has a search index
+pub(crate) fn doc_dir_of(file: &Path) -> Result<PathBuf, Error> {
+    for dir in file.ancestors().skip(1) {
+        if dir.is_dir() && search_index::ls_search_index(dir)?.is_some() {
+            return Ok(dir.into());
+        }
+    }
+    Err(LocationError::DocNotFound.into())
+}
+
+/// Whether the page is of std, and its path relative to the doc dir
+///
+/// `https://doc.rust-lang.org/<channel>/std/...` and `https://docs.rs/<crate>/<version>/...`
+fn web_page(url: &Url) -> Option<(bool, PathBuf)> {
+    let mut segments = url.path_segments()?.collect::<Vec<_>>();
+    if segments.last() == Some(&"") {
+        segments.pop();
+    }
+    let (is_std, rel) = match url.host_str()? {
+        "doc.rust-lang.org" => {
+            let is_channel = |s: &str| {
+                matches!(s, "stable" | "beta" | "nightly")
+                    || s.split('.').all(|n| n.parse::<u32>().is_ok())
+            };
+            if segments.first().copied().is_some_and(is_channel) {
+                segments.remove(0);
+            }
+            (true, segments)
+        }
+        "docs.rs" => match segments.len() {
+            0 => return None,
+            1 | 2 => (false, vec![]),
+            _ => (false, segments.split_off(2))
+        },
+        _ => return None
+    };
+    if rel.is_empty() {
+        let krate = url.path_segments()?.next()?;
+        return Some((
+            is_std,
+            Path::new(&krate.replace('-', "_")).join("index.html")
+        ));
+    }
+    let mut path: PathBuf = rel.into_iter().collect();
+    if path.extension().is_none() {
+        path.push("index.html");
+    }
+    Some((is_std, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_web_pages() {
+        let page = |u: &str| web_page(&Url::parse(u).unwrap());
+        assert_eq!(
+            page("https://doc.rust-lang.org/std/collections/struct.HashMap.html#method.insert"),
+            Some((true, "std/collections/struct.HashMap.html".into()))
+        );
+        assert_eq!(
+            page("https://doc.rust-lang.org/1.60.0/core/fmt/"),
+            Some((true, "core/fmt/index.html".into()))
+        );
+        assert_eq!(
+            page("https://docs.rs/serde-json/latest/serde_json/fn.to_string.html"),
+            Some((false, "serde_json/fn.to_string.html".into()))
+        );
+        assert_eq!(
+            page("https://docs.rs/serde-json"),
+            Some((false, "serde_json/index.html".into()))
+        );
+        assert_eq!(page("https://example.com/std/index.html"), None);
+    }
+
+    #[test]
+    fn which_local_pages() {
+        let tree = crate::fixture::doc_tree();
+        let doc_dir = tree.doc_dir();
+        let manifest = Manifest::default();
+        let url = Url::from_file_path(doc_dir.join("deep/outer/inner/struct.Widget.html"))
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            which(&format!("{}#method.fmt-1", url), &manifest)
+                .unwrap()
+                .line(),
+            "deep::outer::inner::Widget::fmt\tmethod"
+        );
+        let file = doc_dir.join("deep/outer/struct.Widget.html");
+        let found = which(&file.display().to_string(), &manifest).unwrap();
+        assert_eq!(found.line(), "deep::outer::inner::Widget\tstruct");
+        assert_eq!(
+            serde_json::to_value(&found).unwrap()["kind"],
+            serde_json::json!("struct")
+        );
+    }
+}
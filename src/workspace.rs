@@ -0,0 +1,323 @@
+use crate::{search_index::Manifest, Error};
+use cargo_metadata::{semver::Version, Metadata, Package, PackageId, Source, Target, TargetKind};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf
+};
+
+/// How a documented crate relates to the current workspace
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+pub enum CrateKind {
+    Member,
+    Direct,
+    Transitive,
+    Std
+}
+
+impl CrateKind {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            CrateKind::Member => "member",
+            CrateKind::Direct => "direct",
+            CrateKind::Transitive => "transitive",
+            CrateKind::Std => "std"
+        }
+    }
+}
+
+/// Where cargo got the package from
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SourceKind {
+    Registry,
+    Git,
+    Path
+}
+
+impl SourceKind {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            SourceKind::Registry => "registry",
+            SourceKind::Git => "git",
+            SourceKind::Path => "path"
+        }
+    }
+
+    fn new(source: Option<&Source>) -> Self {
+        match source {
+            Some(s) if s.repr.starts_with("git+") => SourceKind::Git,
+            Some(_) => SourceKind::Registry,
+            None => SourceKind::Path
+        }
+    }
+}
+
+/// A package that provides a documented crate
+#[derive(Debug, Clone)]
+pub struct CrateInfo {
+    pub id: PackageId,
+    /// The package name
+    pub name: String,
+    pub kind: CrateKind,
+    pub version: Version,
+    pub source: SourceKind,
+    pub license: Option<String>,
+    pub manifest_path: PathBuf,
+    /// The directory of the crate root such as `src/lib.rs`
+    pub src_dir: PathBuf
+}
+
+/// Crate names as rustdoc writes them, classified by `cargo metadata`
+#[derive(Debug, Default)]
+pub struct Workspace {
+    /// Several semver-incompatible versions may share one crate name
+    crates: HashMap<String, Vec<CrateInfo>>,
+    /// Packages documented by `cargo doc --package`
+    scope: Option<HashSet<PackageId>>
+}
+
+impl Workspace {
+    /// Resolves the whole dependency graph offline
+    pub fn load(manifest: &Manifest) -> Result<Self, Error> {
+        let meta = crate::search_index::metadata(manifest, false)?;
+        let mut ws = Self::from_metadata(&meta);
+        if let Some(spec) = &manifest.package {
+            ws.scope = Some(package_scope(&meta, spec)?);
+        }
+        Ok(ws)
+    }
+
+    pub fn from_metadata(meta: &Metadata) -> Self {
+        let packages: HashMap<&PackageId, &Package> =
+            meta.packages.iter().map(|p| (&p.id, p)).collect();
+        let members: HashSet<&PackageId> = meta.workspace_members.iter().collect();
+        let direct: HashSet<&PackageId> = meta
+            .resolve
+            .iter()
+            .flat_map(|r| r.nodes.iter())
+            .filter(|n| members.contains(&n.id))
+            .flat_map(|n| n.deps.iter().map(|d| &d.pkg))
+            .collect();
+        let mut crates: HashMap<String, Vec<CrateInfo>> = HashMap::new();
+        for (id, package) in packages {
+            let kind = if members.contains(id) {
+                CrateKind::Member
+            } else if direct.contains(id) {
+                CrateKind::Direct
+            } else {
+                CrateKind::Transitive
+            };
+            for target in documented_targets(package) {
+                let info = CrateInfo {
+                    id: id.clone(),
+                    name: package.name.clone(),
+                    kind,
+                    version: package.version.clone(),
+                    source: SourceKind::new(package.source.as_ref()),
+                    license: package.license.clone(),
+                    manifest_path: package.manifest_path.clone().into(),
+                    src_dir: target
+                        .src_path
+                        .parent()
+                        .map_or_else(PathBuf::new, |d| d.into())
+                };
+                let infos = crates.entry(crate_name(target)).or_default();
+                // A bin named after the lib of its package is the same package
+                if !infos.iter().any(|i| i.id == info.id) {
+                    infos.push(info);
+                }
+            }
+        }
+        for infos in crates.values_mut() {
+            infos.sort_by(|a, b| b.version.cmp(&a.version));
+        }
+        Self {
+            crates,
+            scope: None
+        }
+    }
+
+    pub fn in_scope(&self, krate_name: &str) -> bool {
+        match &self.scope {
+            Some(scope) => self.infos(krate_name).iter().any(|i| scope.contains(&i.id)),
+            None => true
+        }
+    }
+
+    /// Returns `None` for crates left in the doc dir but not in the dependency graph
+    pub fn kind(&self, krate_name: &str) -> Option<CrateKind> {
+        if crate::location::is_std_krate(krate_name) {
+            return Some(CrateKind::Std);
+        }
+        self.info(krate_name).map(|i| i.kind)
+    }
+
+    /// The package whose crate the doc dir most likely has when several versions share the name
+    ///
+    /// Prefers packages in the scope of `--package`, then the closest to the workspace, such as
+    /// the version that members depend on over one pulled in by another dependency.
+    pub fn info(&self, krate_name: &str) -> Option<&CrateInfo> {
+        let out_of_scope = |i: &CrateInfo| self.scope.as_ref().is_some_and(|s| !s.contains(&i.id));
+        // Newest first, so that min_by_key keeps the newest of equal keys
+        self.infos(krate_name)
+            .iter()
+            .min_by_key(|i| (out_of_scope(i), i.kind))
+    }
+
+    /// Every package with the crate name, newest version first
+    pub fn infos(&self, krate_name: &str) -> &[CrateInfo] {
+        self.crates.get(krate_name).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// The package matching `name` or `name@version` and all of its dependencies
+fn package_scope(meta: &Metadata, spec: &str) -> Result<HashSet<PackageId>, Error> {
+    let (name, version) = match spec.split_once('@') {
+        Some((name, version)) => (name, Some(version)),
+        None => (spec, None)
+    };
+    let matches =
+        |p: &&Package| p.name == name && version.is_none_or(|v| p.version.to_string() == v);
+    let root = meta
+        .packages
+        .iter()
+        .filter(|p| meta.workspace_members.contains(&p.id))
+        .find(matches)
+        .or_else(|| meta.packages.iter().find(matches))
+        .ok_or_else(|| Error::PackageNotFound(spec.into()))?;
+    let deps: HashMap<&PackageId, Vec<&PackageId>> = meta
+        .resolve
+        .iter()
+        .flat_map(|r| r.nodes.iter())
+        .map(|n| (&n.id, n.deps.iter().map(|d| &d.pkg).collect()))
+        .collect();
+    let mut visited: HashSet<&PackageId> = HashSet::new();
+    let mut stack = vec![&root.id];
+    while let Some(id) = stack.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+        stack.extend(deps.get(id).into_iter().flatten());
+    }
+    Ok(visited.into_iter().cloned().collect())
+}
+
+fn documented_targets(package: &Package) -> impl Iterator<Item = &Target> {
+    package.targets.iter().filter(|t| {
+        t.kind.iter().any(|k| {
+            matches!(
+                k,
+                TargetKind::Lib
+                    | TargetKind::RLib
+                    | TargetKind::DyLib
+                    | TargetKind::CDyLib
+                    | TargetKind::StaticLib
+                    | TargetKind::ProcMacro
+                    | TargetKind::Bin
+            )
+        })
+    })
+}
+
+/// Rustdoc names a crate after its target, not after the package or a renamed dependency
+fn crate_name(target: &Target) -> String { target.name.replace('-', "_") }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, target: &str, kind: &str) -> String {
+        versioned(name, "0.1.0", target, kind)
+    }
+
+    fn versioned(name: &str, version: &str, target: &str, kind: &str) -> String {
+        format!(
+            r#"{{
+                "name": "{name}",
+                "version": "{version}",
+                "id": "{name} {version}",
+                "dependencies": [],
+                "targets": [{{"name": "{target}", "kind": ["{kind}"], "src_path": "/src/lib.rs"}}],
+                "features": {{}},
+                "manifest_path": "/{name}/Cargo.toml"
+            }}"#
+        )
+    }
+
+    #[test]
+    fn classify() {
+        let json = format!(
+            r#"{{
+                "packages": [{}, {}, {}, {}, {}, {}],
+                "workspace_members": ["app 0.1.0"],
+                "resolve": {{
+                    "nodes": [
+                        {{"id": "app 0.1.0", "dependencies": [], "deps": [
+                            {{"name": "renamed", "pkg": "serde-json 0.1.0"}},
+                            {{"name": "derive", "pkg": "my-derive 0.1.0"}}
+                        ]}},
+                        {{"id": "serde-json 0.1.0", "dependencies": [], "deps": [
+                            {{"name": "windows_x86_64_msvc", "pkg": "windows_x86_64_msvc 0.1.0"}},
+                            {{"name": "my_derive", "pkg": "my-derive 0.2.0"}}
+                        ]}}
+                    ],
+                    "root": null
+                }},
+                "workspace_root": "/",
+                "target_directory": "/target",
+                "version": 1
+            }}"#,
+            package("app", "app", "bin"),
+            package("serde-json", "serde-json", "lib"),
+            package("my-derive", "my-derive", "proc-macro"),
+            package("windows_x86_64_msvc", "windows_x86_64_msvc", "lib"),
+            versioned("my-derive", "0.2.0", "my-derive", "proc-macro"),
+            // Edition 2024, with a bin of the same name as the lib
+            r#"{
+                "name": "idna_adapter",
+                "version": "1.2.0",
+                "id": "idna_adapter 1.2.0",
+                "dependencies": [],
+                "targets": [
+                    {"name": "idna_adapter", "kind": ["lib"], "src_path": "/idna_adapter/src/lib.rs", "edition": "2024"},
+                    {"name": "idna_adapter", "kind": ["bin"], "src_path": "/idna_adapter/src/main.rs", "edition": "2024"}
+                ],
+                "features": {},
+                "manifest_path": "/idna_adapter/Cargo.toml",
+                "edition": "2024"
+            }"#
+        );
+        let meta: Metadata = serde_json::from_str(&json).unwrap();
+        let ws = Workspace::from_metadata(&meta);
+        assert_eq!(ws.kind("app"), Some(CrateKind::Member));
+        assert_eq!(ws.kind("serde_json"), Some(CrateKind::Direct));
+        assert_eq!(ws.kind("my_derive"), Some(CrateKind::Direct));
+        assert_eq!(ws.kind("windows_x86_64_msvc"), Some(CrateKind::Transitive));
+        assert_eq!(ws.kind("core"), Some(CrateKind::Std));
+        assert_eq!(ws.kind("renamed"), None);
+        let info = &ws.infos("serde_json")[0];
+        assert_eq!(info.version, Version::new(0, 1, 0));
+        assert_eq!(info.source, SourceKind::Path);
+        assert_eq!(info.src_dir, PathBuf::from("/src"));
+        assert!(ws.infos("core").is_empty());
+        let adapter = ws.infos("idna_adapter");
+        assert_eq!(adapter.len(), 1);
+        assert_eq!(adapter[0].kind, CrateKind::Transitive);
+        assert_eq!(adapter[0].src_dir, PathBuf::from("/idna_adapter/src"));
+        let git = Source {
+            repr: "git+https://github.com/octaltree/rustdoc-index#0123abc".into()
+        };
+        assert_eq!(SourceKind::new(Some(&git)), SourceKind::Git);
+        // The version that the member depends on, though a dependency pulls in a newer one
+        assert_eq!(ws.infos("my_derive").len(), 2);
+        assert_eq!(ws.info("my_derive").unwrap().version, Version::new(0, 1, 0));
+        let id = |repr: &str| PackageId { repr: repr.into() };
+        let mut ws = Workspace::from_metadata(&meta);
+        ws.scope = Some(package_scope(&meta, "serde-json").unwrap());
+        assert!(ws.in_scope("serde_json"));
+        assert!(ws.in_scope("windows_x86_64_msvc"));
+        assert!(!ws.in_scope("app"));
+        assert!(ws.scope.as_ref().unwrap().contains(&id("my-derive 0.2.0")));
+        assert_eq!(ws.info("my_derive").unwrap().version, Version::new(0, 2, 0));
+        assert!(package_scope(&meta, "serde-json@0.2.0").is_err());
+    }
+}